@@ -1,6 +1,8 @@
+use std::io::Write;
+
 use anyhow::Result;
 
-use crate::{data::Data, rule::Rule};
+use crate::{data::Data, export::Exporter, rule::Rule};
 
 pub struct App {
     pub data: Data,
@@ -15,10 +17,19 @@ impl App {
         })
     }
 
-    #[cfg(feature = "excel")]
-    pub fn export_excel(&self) -> Result<()> {
-        self.data.export_excel(&self.file_name)?;
-        Ok(())
+    /// Writes the document with the exporter.
+    pub fn export(&self, exporter: &dyn Exporter) -> Result<()> {
+        exporter.export(&self.data, &self.file_name)
+    }
+
+    /// Writes the document into `writer` with the exporter instead of the file.
+    pub fn write(&self, exporter: &dyn Exporter, writer: &mut dyn Write) -> Result<()> {
+        exporter.write(&self.data, writer)
+    }
+
+    /// Returns the path of the file written by the exporter.
+    pub fn output_path(&self, exporter: &dyn Exporter) -> String {
+        format!("{}.{}", self.file_name, exporter.extension())
     }
 }
 
@@ -26,10 +37,70 @@ impl App {
 mod tests {
     use super::*;
 
+    use crate::export::*;
+
+    #[cfg(feature = "excel")]
     #[test]
     fn test_export_excel() {
         let file_name = "unit_test";
         let app = App::new(file_name, "# test", Rule::default()).unwrap();
-        assert!(app.export_excel().is_ok());
+        assert!(app.export(&ExcelExporter).is_ok());
+    }
+
+    #[test]
+    fn test_export_csv() {
+        let file_name = "unit_test_app";
+        let app = App::new(file_name, "# test", Rule::default()).unwrap();
+        assert!(app.export(&CsvExporter).is_ok());
+    }
+
+    #[test]
+    fn test_export_json() {
+        let file_name = "unit_test_app";
+        let app = App::new(file_name, "# test", Rule::default()).unwrap();
+        assert!(app.export(&JsonExporter).is_ok());
+        std::fs::remove_file(format!("{}.json", file_name)).unwrap();
+    }
+
+    #[test]
+    fn test_write() {
+        let app = App::new("unit_test_app", "# test", Rule::default()).unwrap();
+        let mut written = vec![];
+        assert!(app.write(&JsonExporter, &mut written).is_ok());
+        assert_eq!(
+            serde_json::to_string_pretty(&json::to_json(&app.data)).unwrap(),
+            String::from_utf8(written).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_output_path() {
+        let app = App::new("unit_test_app", "# test", Rule::default()).unwrap();
+        assert_eq!("unit_test_app.html", app.output_path(&HtmlExporter));
+    }
+
+    #[test]
+    fn test_export_html() {
+        let file_name = "unit_test_app_html";
+        let app = App::new(file_name, "# test", Rule::default()).unwrap();
+        assert!(app.export(&HtmlExporter).is_ok());
+        std::fs::remove_file(format!("{}.html", file_name)).unwrap();
+    }
+
+    #[test]
+    fn test_export_markdown() {
+        let file_name = "unit_test_app_markdown";
+        let app = App::new(file_name, "# test", Rule::default()).unwrap();
+        assert!(app.export(&MarkdownExporter).is_ok());
+        std::fs::remove_file(format!("{}.md", file_name)).unwrap();
+    }
+
+    #[cfg(feature = "ods")]
+    #[test]
+    fn test_export_ods() {
+        let file_name = "unit_test_app_ods";
+        let app = App::new(file_name, "# test", Rule::default()).unwrap();
+        assert!(app.export(&OdsExporter).is_ok());
+        std::fs::remove_file(format!("{}.ods", file_name)).unwrap();
     }
 }
@@ -1,46 +1,128 @@
 #![warn(rust_2018_idioms)]
 
-use std::{env, fs, io::Write, path::Path};
+use std::{
+    env, fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use chrono::Local;
-use clap::{crate_authors, crate_description, crate_name, crate_version, App as ClapApp, Arg};
+use clap::{
+    crate_authors, crate_description, crate_name, crate_version, App as ClapApp, AppSettings, Arg,
+    ArgMatches, SubCommand,
+};
 use log::{debug, info};
 
-use md_designer::{app::App, rule::Rule, utils::get_output_filename};
+use md_designer::{
+    app::App, export::Registry, import::import_excel, lint::lint as lint_markdown, rule::Rule,
+    source::ParseError, utils::get_output_filename, watcher::Watcher,
+};
+
+/// the input path to read from stdin
+const STDIN: &str = "-";
+
+/// the output file name used if the input is read from stdin and `-o` is not given
+const STDIN_OUTPUT_FILENAME: &str = "output";
+
+/// interval of checking the modification of the watched files
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
 
 fn main() -> Result<()> {
+    // the output formats. the first one is the default
+    let registry = Registry::builtin();
+    let formats = registry.names();
     // setup clap
     let clap = ClapApp::new(crate_name!())
         .author(crate_authors!())
         .version(crate_version!())
         .about(crate_description!())
+        .setting(AppSettings::SubcommandsNegateReqs)
         .arg(
             Arg::with_name("path")
                 .required(true)
-                .help("input file path (.md)"),
+                .help("input file path (.md). '-' reads from stdin."),
         )
         .arg(
             Arg::with_name("conf_path")
                 .required(true)
                 .help("config file path (.yml)"),
         )
+        .arg(
+            Arg::with_name("stdout")
+                .long("stdout")
+                .conflicts_with_all(&["output_filename", "watch"])
+                .help("writes the output to stdout instead of the file"),
+        )
         .arg(
             Arg::with_name("output_filename")
                 .short("o")
                 .takes_value(true)
-                .help("output file name. '.xlsx' is optional."),
+                .help("output file name. the extension is optional."),
+        )
+        .arg(
+            Arg::with_name("format")
+                .short("f")
+                .long("format")
+                .takes_value(true)
+                .possible_values(&formats)
+                .default_value(formats[0])
+                .help("output format"),
+        )
+        .arg(
+            Arg::with_name("watch")
+                .short("w")
+                .long("watch")
+                .help("regenerates the output whenever the input file or the rule file changes"),
         )
         .arg(
             Arg::with_name("verbose")
                 .short("v")
+                .global(true)
                 .help("verbose (print errors/warnings/info logs)"),
         )
         .arg(
             Arg::with_name("very_verbose")
                 .short("d")
+                .global(true)
                 .help("very verbose (also print debug logs)"),
         )
+        .subcommand(
+            SubCommand::with_name("import")
+                .about("converts .xlsx generated by mddoc back into .md")
+                .arg(
+                    Arg::with_name("path")
+                        .required(true)
+                        .help("input file path (.xlsx)"),
+                )
+                .arg(
+                    Arg::with_name("conf_path")
+                        .required(true)
+                        .help("config file path (.yml)"),
+                )
+                .arg(
+                    Arg::with_name("output_filename")
+                        .short("o")
+                        .takes_value(true)
+                        .help("output file path. defaults to the input file name with '.md'."),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("lint")
+                .about("checks the structure of .md without generating the output")
+                .arg(
+                    Arg::with_name("path")
+                        .required(true)
+                        .help("input file path (.md). '-' reads from stdin."),
+                )
+                .arg(
+                    Arg::with_name("conf_path")
+                        .required(true)
+                        .help("config file path (.yml)"),
+                ),
+        )
         .get_matches();
 
     // setup logging
@@ -68,9 +150,33 @@ fn main() -> Result<()> {
             .init();
     }
 
+    if let Some(matches) = clap.subcommand_matches("import") {
+        return import(matches);
+    }
+    if let Some(matches) = clap.subcommand_matches("lint") {
+        return lint(matches);
+    }
+
+    if clap.is_present("watch") {
+        if clap.value_of("path") == Some(STDIN) {
+            return Err(anyhow!(
+                "stdin can not be watched. Specify the input file path."
+            ));
+        }
+        watch(&clap, &registry);
+    }
+
+    convert(&clap, &registry)?;
+
+    info!("DONE");
+    Ok(())
+}
+
+/// Converts the input file into the output file in the specified format.
+fn convert(clap: &ArgMatches<'_>, registry: &Registry) -> Result<()> {
     let path = Path::new(clap.value_of("path").unwrap());
     info!("input file: {:?}", &path);
-    let input_text = fs::read_to_string(&path)?;
+    let input_text = read_input(path)?;
     debug!("input file content: \n{}", &input_text);
     let cfg_path = Path::new(clap.value_of("conf_path").unwrap());
     info!("rule file: {:?}", &cfg_path);
@@ -79,21 +185,172 @@ fn main() -> Result<()> {
 
     let rule = Rule::marshal(&cfg_text)?;
 
-    let app = App::new(
-        get_output_filename(
-            clap.value_of("output_filename").unwrap_or(
-                path.file_stem()
-                    .with_context(|| "Input file path is malformed")?
-                    .to_str()
-                    .unwrap(),
-            ),
-        )?,
-        &input_text,
-        rule,
-    )?;
-
-    app.export_excel()?;
+    let format = clap.value_of("format").unwrap();
+    let exporter = registry
+        .get(format)
+        .with_context(|| format!("unsupported output format: {}", format))?;
+    let output_filename = get_output_filename(match clap.value_of("output_filename") {
+        Some(output_filename) => output_filename,
+        None if is_stdin(path) => STDIN_OUTPUT_FILENAME,
+        None => path
+            .file_stem()
+            .with_context(|| "Input file path is malformed")?
+            .to_str()
+            .unwrap(),
+    })?;
+
+    let file = input_name(path);
+    let app = App::new(output_filename, &input_text, rule)
+        // point the error to the input file
+        .map_err(|e| match e.downcast::<ParseError>() {
+            Ok(error) => error.with_file(&file).into(),
+            Err(e) => e,
+        })?;
+    for warning in app.data.warnings().iter() {
+        eprintln!("warning: {}", warning.clone().with_file(&file));
+    }
+    for violation in app.data.violations().iter() {
+        eprintln!("warning: {}", violation);
+    }
+    for warning in exporter.warnings(&app.data)?.iter() {
+        eprintln!("warning: {}", warning);
+    }
+
+    if clap.is_present("stdout") {
+        let stdout = io::stdout();
+        let mut handle = stdout.lock();
+        return match app
+            .write(exporter, &mut handle)
+            .and_then(|()| Ok(handle.flush()?))
+        {
+            // the reader of the pipeline (e.g. `head`) may stop reading before the end
+            Err(e)
+                if e.downcast_ref::<io::Error>()
+                    .is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe) =>
+            {
+                Ok(())
+            }
+            result => result,
+        };
+    }
+    // the output must not overwrite the input (e.g. the markdown output)
+    let output = PathBuf::from(app.output_path(exporter));
+    if !is_stdin(path) && output.exists() && fs::canonicalize(&output)? == fs::canonicalize(path)? {
+        return Err(anyhow!(
+            "{:?} is the input file. Specify the output file path with '-o'.",
+            output
+        ));
+    }
+    app.export(exporter)
+}
+
+fn is_stdin(path: &Path) -> bool {
+    path == Path::new(STDIN)
+}
+
+/// Reads the input file, or stdin if the path is `-`.
+fn read_input(path: &Path) -> Result<String> {
+    if is_stdin(path) {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .with_context(|| "could not read stdin")?;
+        Ok(input)
+    } else {
+        Ok(fs::read_to_string(path)?)
+    }
+}
+
+/// Returns the name of the input shown in the errors.
+fn input_name(path: &Path) -> String {
+    if is_stdin(path) {
+        String::from("<stdin>")
+    } else {
+        path.to_string_lossy().to_string()
+    }
+}
+
+/// Converts the input file every time the input file or the rule file is modified.
+/// Errors are printed and the watching continues, so it never returns.
+fn watch(clap: &ArgMatches<'_>, registry: &Registry) -> ! {
+    let path = Path::new(clap.value_of("path").unwrap());
+    let cfg_path = Path::new(clap.value_of("conf_path").unwrap());
+    let mut watcher = Watcher::new(&[path, cfg_path]);
+    let run = || match convert(clap, registry) {
+        Ok(()) => println!("[{}] generated", Local::now().format("%H:%M:%S")),
+        Err(e) => eprintln!("[{}] error: {:#}", Local::now().format("%H:%M:%S"), e),
+    };
+    run();
+    println!(
+        "watching {:?} and {:?}... (press Ctrl+C to stop)",
+        path, cfg_path
+    );
+    loop {
+        thread::sleep(WATCH_INTERVAL);
+        let changed = watcher.changed();
+        if !changed.is_empty() {
+            println!("changed: {:?}", changed);
+            run();
+        }
+    }
+}
+
+fn import(matches: &ArgMatches<'_>) -> Result<()> {
+    let path = Path::new(matches.value_of("path").unwrap());
+    info!("input file: {:?}", &path);
+    let cfg_path = Path::new(matches.value_of("conf_path").unwrap());
+    info!("rule file: {:?}", &cfg_path);
+    let cfg_text = fs::read_to_string(cfg_path)?;
+    debug!("rule file content: \n{}", &cfg_text);
+
+    let rule = Rule::marshal(&cfg_text)?;
+    let markdown = import_excel(path, &rule)?;
+
+    let output = match matches.value_of("output_filename") {
+        Some(output) => PathBuf::from(output),
+        None => {
+            // do not overwrite the original markdown silently
+            let output = path.with_extension("md");
+            if output.exists() {
+                return Err(anyhow!(
+                    "{:?} already exists. Specify the output file path with '-o'.",
+                    output
+                ));
+            }
+            output
+        }
+    };
+    info!("output file: {:?}", &output);
+    fs::write(&output, markdown)?;
 
     info!("DONE");
     Ok(())
 }
+
+/// Prints the structural issues of the input file.
+/// Returns an error if any issue is found so that the exit code is non-zero.
+fn lint(matches: &ArgMatches<'_>) -> Result<()> {
+    let path = Path::new(matches.value_of("path").unwrap());
+    info!("input file: {:?}", &path);
+    let input_text = read_input(path)?;
+    let cfg_path = Path::new(matches.value_of("conf_path").unwrap());
+    info!("rule file: {:?}", &cfg_path);
+    let cfg_text = fs::read_to_string(cfg_path)?;
+    debug!("rule file content: \n{}", &cfg_text);
+
+    let rule = Rule::marshal(&cfg_text)?;
+    let file = input_name(path);
+    let issues =
+        lint_markdown(&input_text, rule).map_err(|e| match e.downcast::<ParseError>() {
+            Ok(error) => error.with_file(&file).into(),
+            Err(e) => e,
+        })?;
+    for issue in issues.iter() {
+        eprintln!("{}\n", issue.clone().with_file(&file));
+    }
+    if !issues.is_empty() {
+        return Err(anyhow!("{} issue(s) found in {:?}", issues.len(), file));
+    }
+    println!("no issues found in {:?}", file);
+    Ok(())
+}
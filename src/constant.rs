@@ -3,4 +3,20 @@ use lazy_static::lazy_static;
 lazy_static! {
     pub static ref AUTO_INCREMENT_KEY: String = String::from("AUTOINCREMENT");
     pub static ref CUSTOM_PREFIX_KEY: String = String::from("CUSTOMPREFIX");
+    pub static ref OVERFLOW_KEY: String = String::from("OVERFLOW");
 }
+
+/// extensions stripped from the output file name
+pub const OUTPUT_EXTENSIONS: &[&str] = &[".xlsx", ".csv", ".json", ".html", ".ods", ".md"];
+
+/// style sheet embedded in the html output
+pub const HTML_STYLE: &str = "body { font-family: sans-serif; margin: 2em; }
+nav a { margin-right: 1em; }
+details { margin: 1em 0; }
+summary { font-size: 1.4em; font-weight: bold; cursor: pointer; }
+table { border-collapse: collapse; margin: 0.5em 0 1.5em; }
+th, td { border: 1px solid #999; padding: 0.3em 0.6em; text-align: left; vertical-align: top; }
+th { background: #00FFFF; text-align: center; vertical-align: middle; }
+td { white-space: pre-wrap; }
+td.violation { background: #FFC7CE; }
+footer { margin-top: 2em; color: #666; text-align: center; }";
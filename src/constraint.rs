@@ -0,0 +1,238 @@
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+use regex::Regex;
+
+use crate::{
+    data::Sheet,
+    rule::{self, Rule},
+};
+
+/// A cell violating the constraints of its column in the rule.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Violation {
+    pub sheet: String,
+    pub block: String,
+    /// 1-based row number in the block
+    pub row: usize,
+    /// column title
+    pub column: String,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}\n --> sheet '{}', block '{}', row {}",
+            self.message, self.sheet, self.block, self.row
+        )
+    }
+}
+
+/// The constraints of the columns in a block (`required`, `maxLength`, `pattern` and `enum`).
+#[derive(Debug)]
+pub struct Constraints<'a> {
+    columns: &'a [rule::Column],
+    /// compiled `pattern` of each column
+    patterns: Vec<Option<Regex>>,
+}
+
+impl<'a> Constraints<'a> {
+    pub fn new(columns: &'a [rule::Column]) -> Result<Self> {
+        let patterns = columns
+            .iter()
+            .map(|column| match &column.pattern {
+                // the whole cell must match
+                Some(pattern) => Regex::new(&format!("^(?:{})$", pattern))
+                    .map(Some)
+                    .map_err(|e| anyhow!("invalid pattern of column '{}': {}", column.title, e)),
+                None => Ok(None),
+            })
+            .collect::<Result<Vec<Option<Regex>>>>()?;
+        Ok(Constraints { columns, patterns })
+    }
+
+    /// Returns the column index and the message of each cell violating the constraints.
+    /// Empty rows are not checked.
+    pub fn check(&self, cells: &[String]) -> Vec<(usize, String)> {
+        // auto incremented ids are filled even in empty rows
+        let is_empty_row = self
+            .columns
+            .iter()
+            .zip(cells.iter())
+            .all(|(column, cell)| column.auto_increment || cell.trim().is_empty());
+        if is_empty_row {
+            return vec![];
+        }
+        let mut violations = vec![];
+        for (idx, (column, cell)) in self.columns.iter().zip(cells.iter()).enumerate() {
+            if let Some(message) = self.check_cell(idx, column, cell) {
+                violations.push((idx, message));
+            }
+        }
+        violations
+    }
+
+    fn check_cell(&self, idx: usize, column: &rule::Column, cell: &str) -> Option<String> {
+        if cell.trim().is_empty() {
+            return if column.required {
+                Some(format!("column '{}' is required but empty.", column.title))
+            } else {
+                None
+            };
+        }
+        if let Some(max_length) = column.max_length {
+            let length = cell.chars().count();
+            if length > max_length {
+                return Some(format!(
+                    "column '{}' must be at most {} characters, but has {}.",
+                    column.title, max_length, length
+                ));
+            }
+        }
+        if let (Some(pattern), Some(regex)) = (&column.pattern, &self.patterns[idx]) {
+            if !regex.is_match(cell) {
+                return Some(format!(
+                    "column '{}' does not match the pattern '{}': {:?}",
+                    column.title, pattern, cell
+                ));
+            }
+        }
+        if let Some(values) = &column.enum_values {
+            if !values.iter().any(|value| value == cell) {
+                return Some(format!(
+                    "column '{}' must be one of {}, but got {:?}",
+                    column.title,
+                    values.join(", "),
+                    cell
+                ));
+            }
+        }
+        None
+    }
+}
+
+/// Checks every row of the sheets with the constraints of the columns in the rule.
+pub fn check(sheets: &[Sheet], rule: &Rule) -> Result<Vec<Violation>> {
+    let constraints = rule
+        .doc
+        .blocks
+        .iter()
+        .map(|block| Constraints::new(&block.columns))
+        .collect::<Result<Vec<Constraints<'_>>>>()?;
+    let mut violations = vec![];
+    for sheet in sheets.iter() {
+        for block in sheet.blocks().iter() {
+            let constraints = match constraints.get(block.rule_idx()) {
+                Some(constraints) => constraints,
+                None => continue,
+            };
+            for (row_idx, row) in block.rows().iter().enumerate() {
+                for (column_idx, message) in constraints.check(row.cells()) {
+                    violations.push(Violation {
+                        sheet: sheet.name().unwrap_or_default().to_string(),
+                        block: block.title().to_string(),
+                        row: row_idx + 1,
+                        column: constraints.columns[column_idx].title.clone(),
+                        message,
+                    });
+                }
+            }
+        }
+    }
+    Ok(violations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|cell| cell.to_string()).collect()
+    }
+
+    #[test]
+    fn test_check() {
+        let columns = vec![
+            rule::Column {
+                title: String::from("No"),
+                auto_increment: true,
+                ..Default::default()
+            },
+            rule::Column {
+                title: String::from("Title"),
+                required: true,
+                max_length: Some(5),
+                ..Default::default()
+            },
+            rule::Column {
+                title: String::from("Ticket"),
+                pattern: Some(String::from("[A-Z]+-[0-9]+")),
+                ..Default::default()
+            },
+            rule::Column {
+                title: String::from("Priority"),
+                enum_values: Some(vec![String::from("High"), String::from("Low")]),
+                ..Default::default()
+            },
+        ];
+        let constraints = Constraints::new(&columns).unwrap();
+        assert!(constraints
+            .check(&cells(&["1", "Login", "QA-1", "High"]))
+            .is_empty());
+        // optional cells can be empty
+        assert!(constraints
+            .check(&cells(&["1", "Login", "", ""]))
+            .is_empty());
+        // empty rows are not checked
+        assert!(constraints.check(&cells(&["1", "", "", ""])).is_empty());
+        assert_eq!(
+            vec![
+                (
+                    1,
+                    String::from("column 'Title' must be at most 5 characters, but has 6.")
+                ),
+                (
+                    2,
+                    String::from(
+                        "column 'Ticket' does not match the pattern '[A-Z]+-[0-9]+': \"xQA-1\""
+                    )
+                ),
+                (
+                    3,
+                    String::from("column 'Priority' must be one of High, Low, but got \"high\"")
+                ),
+            ],
+            constraints.check(&cells(&["1", "Logout", "xQA-1", "high"]))
+        );
+        assert_eq!(
+            vec![(1, String::from("column 'Title' is required but empty."))],
+            constraints.check(&cells(&["1", "", "QA-1", ""]))
+        );
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let columns = vec![rule::Column {
+            pattern: Some(String::from("[a-")),
+            ..Default::default()
+        }];
+        assert!(Constraints::new(&columns).is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        let violation = Violation {
+            sheet: String::from("Sheet"),
+            block: String::from("Block"),
+            row: 2,
+            column: String::from("Title"),
+            message: String::from("something wrong"),
+        };
+        assert_eq!(
+            "something wrong\n --> sheet 'Sheet', block 'Block', row 2",
+            violation.to_string()
+        );
+    }
+}
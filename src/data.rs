@@ -4,32 +4,30 @@ use log::{debug, info};
 #[cfg(test)]
 use std::{println as info, println as debug};
 
-use anyhow::{anyhow, Result};
+use anyhow::Result;
+use lazy_static::lazy_static;
 use pulldown_cmark::{CowStr, Event, Options, Parser, Tag};
+use regex::Regex;
+use serde::Serialize;
+
+use std::collections::{BTreeMap, HashMap};
 
 use crate::{
+    constraint::{self, Violation},
     mapping::Mapping,
-    rule::Rule,
-    utils::{custom_prefix_to_key, get_custom_prefix_end_idx},
+    rule::{self, Rule, Unmapped},
+    source::{ParseError, SourceMap},
+    utils::{
+        custom_prefix_to_key, get_custom_prefix_as_normal_list, get_custom_prefix_end_idx,
+        is_code_fence,
+    },
 };
 
-#[cfg(feature = "excel")]
-use xlsxwriter::*;
-
-#[derive(Debug)]
-pub struct CellRange {
-    from: u16,
-    to: u16,
-}
-
-impl CellRange {
-    pub fn new(from: u16, to: u16) -> Self {
-        CellRange { from, to }
-    }
-
-    pub fn contain(&self, pos: u16) -> bool {
-        self.from <= pos && pos <= self.to
-    }
+lazy_static! {
+    /// `**bold**` and `*italic*`, which can't start or end with whitespace in the same way as `_`
+    static ref EMPHASIS: Regex =
+        Regex::new(r"\*\*[^\s*](?:[^*]*[^\s*])?\*\*|\*[^\s*](?:[^*]*[^\s*])?\*")
+            .expect("Invalid regex");
 }
 
 #[derive(Debug, PartialEq)]
@@ -37,6 +35,8 @@ pub struct Data {
     sheets: Vec<Sheet>,
     rule: Rule,
     mapping: Mapping,
+    warnings: Vec<ParseError>,
+    violations: Vec<Violation>,
 }
 
 impl Default for Data {
@@ -45,19 +45,55 @@ impl Default for Data {
             sheets: vec![],
             rule: Rule::default(),
             mapping: Mapping::default(),
+            warnings: vec![],
+            violations: vec![],
         }
     }
 }
 
 impl Data {
+    /// Returns the parsed sheets.
+    pub fn sheets(&self) -> &[Sheet] {
+        &self.sheets
+    }
+
+    /// Returns the rule used for parsing.
+    pub fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    /// Returns the content skipped while parsing with `unmapped: warning` in the rule.
+    pub fn warnings(&self) -> &[ParseError] {
+        &self.warnings
+    }
+
+    /// Returns the cells violating the constraints of the columns in the rule.
+    pub fn violations(&self) -> &[Violation] {
+        &self.violations
+    }
+
+    /// Returns the column definitions of the `block_idx`-th block in the rule.
+    /// It can be passed to `Row::get`, `Row::id` and `Row::to_map`.
+    pub fn columns(&self, block_idx: usize) -> &[rule::Column] {
+        match self.rule.doc.blocks.get(block_idx) {
+            Some(block) => &block.columns,
+            None => &[],
+        }
+    }
+
+    /// Parses the input with the rule.
+    /// Errors in the input are returned as `ParseError` pointing to the location.
     pub fn marshal(input: &str, rule: Rule) -> Result<Self> {
+        let original = input;
         // escape md notation without beginnig of line
         info!("escape input");
-        let input = Data::escape_notation(input);
+        let escaped = Data::escape_notation(input);
 
         info!("parsing input with parsed rules...");
         // trim first empty lines
-        let input = input.trim_start();
+        let input = escaped.trim_start();
+        // the escaped input has an extra empty line at the beginning
+        let first_line = escaped[..escaped.len() - input.len()].matches('\n').count() - 1;
 
         // convert the rule into mapping
         let mapping = Mapping::new(&rule)?;
@@ -65,15 +101,34 @@ impl Data {
         // check is first line is Heading(1)
         // (sheet name is required)
         if !input.starts_with("# ") {
-            return Err(anyhow!("input must start with '# ' (sheet name)."));
+            return Err(ParseError::new(
+                first_line,
+                original.split('\n').nth(first_line).unwrap_or(""),
+                "input must start with '# ' (sheet name).",
+            )
+            .into());
         }
 
-        let input = rule.filter(input);
+        // select the block in the rule for each block in the input
+        let selected = rule.select_blocks(original)?;
+        let block_idx = |sheet_idx: usize, idx: usize| {
+            selected
+                .get(sheet_idx)
+                .and_then(|blocks| blocks.get(idx))
+                .copied()
+                .unwrap_or(idx)
+        };
+
+        let filtered = rule.filter(input);
+        let source_map = SourceMap::new(original, first_line, input, &filtered);
+        let input = filtered;
 
         // marshal
         // expand parser to be able to handle 7th heading
         let mut options = Options::empty();
         options.insert(Options::ENABLE_TASKLISTS);
+        options.insert(Options::ENABLE_STRIKETHROUGH);
+        let warnings = Data::check(&input, &source_map, &rule, &mapping, &selected, options)?;
         //let input = Data::custom_filter(input);
         let parser = Parser::new_ext(&input, options);
         let mut parser_filtered = vec![];
@@ -97,7 +152,10 @@ impl Data {
         });
 
         let mut current_sheet: usize = 0;
-        let mut current_block: usize = 0;
+        // index of the block in the sheet
+        let mut block_in_sheet: usize = 0;
+        // index of the block in the rule
+        let mut current_block: usize = block_idx(0, 0);
         let mut current_column: usize = 0;
         let mut current_row = 1;
         // Column idx in the previous loop.
@@ -107,7 +165,10 @@ impl Data {
         let mut previous_is_list = false;
         let mut sheets = vec![];
         let mut sheet = Sheet::default();
-        let mut block = Block::default();
+        let mut block = Block {
+            rule_idx: current_block,
+            ..Default::default()
+        };
         let mut row = Row::new(current_block, &mapping);
         // new line should be started in current loop?
         let mut start_new_line = false;
@@ -115,12 +176,47 @@ impl Data {
         let mut is_sheet_name = false;
         // is the first row since the new block started?
         let mut block_start = false;
+        // inline formatting applied to the next text
+        let mut style = Style::default();
+        // is the previous event inline content in the same line?
+        // if true, next text is appended to the current line.
+        let mut previous_is_inline = false;
+        // is each nested list mapped to any column?
+        let mut lists_mapped = vec![];
+        // skip the text since no column is mapped to it
+        let mut skip_text = false;
 
         parser_filtered.iter().for_each(|event| {
             // if true, next text data is append to current column
             debug!("event: {:?}", event);
             match event {
+                Event::Start(Tag::Emphasis) => style.italic = true,
+                Event::Start(Tag::Strong) => style.bold = true,
+                Event::Start(Tag::Strikethrough) => style.strikethrough = true,
+                Event::Start(Tag::Link(_, url, _)) => style.link = Some(url.to_string()),
+                Event::End(Tag::Emphasis) => style.italic = false,
+                Event::End(Tag::Strong) => style.bold = false,
+                Event::End(Tag::Strikethrough) => style.strikethrough = false,
+                Event::End(Tag::Link(..)) => style.link = None,
+                Event::Code(code) => {
+                    let code = Data::reverse_escape_notation(code);
+                    if is_sheet_name {
+                        if let Some(sheet_name) = sheet.sheet_name.as_mut() {
+                            sheet_name.push_str(&code);
+                        }
+                    } else {
+                        let code_style = Style {
+                            code: true,
+                            ..style.clone()
+                        };
+                        if !skip_text {
+                            row.push_text(current_column, &code, &code_style, !previous_is_inline);
+                        }
+                    }
+                    previous_is_inline = true;
+                }
                 Event::Start(tag) => {
+                    previous_is_inline = false;
                     // check previous tag id
                     // if current tag id is smaller than previous one or equal, start new line
                     if let Some(current_idx) = mapping.get_idx(current_block, Some(&tag), None) {
@@ -147,7 +243,7 @@ impl Data {
                             // insert auto incremented id if rule exists
                             debug!("start a new line (Event::Start)");
                             if let Some(id_idx) = mapping.get_auto_increment_idx(current_block) {
-                                row.columns[*id_idx] = format!("{}", current_row);
+                                row.columns[*id_idx] = rule.general.format_id(current_row);
                             }
                             block.rows.push(row.clone());
                             row = Row::new(current_block, &mapping);
@@ -155,20 +251,47 @@ impl Data {
                             previous_idx = 0;
                             current_row += 1;
                         }
+                        if let Tag::List(_) = tag {
+                            lists_mapped
+                                .push(mapping.get_idx(current_block, Some(tag), None).is_some());
+                        }
+                        let is_unmapped = match tag {
+                            Tag::Heading(_) => true,
+                            Tag::Item => lists_mapped.last() == Some(&false),
+                            _ => false,
+                        };
                         if let Some(column_idx) = mapping.get_idx(current_block, Some(&tag), None) {
                             current_column = *column_idx;
+                            skip_text = false;
+                        } else if is_unmapped && current_block < rule.doc.blocks.len() {
+                            // `unmapped: error` is reported by `Data::check` before
+                            match (
+                                rule.general.unmapped,
+                                mapping.get_overflow_idx(current_block),
+                            ) {
+                                (Unmapped::Overflow, Some(overflow_idx)) => {
+                                    current_column = *overflow_idx;
+                                    skip_text = false;
+                                }
+                                _ => skip_text = true,
+                            }
                         }
                     }
                 }
                 Event::Text(text) => {
-                    if is_sheet_name {
+                    if is_sheet_name && previous_is_inline {
+                        // the rest of the sheet name split by inline formatting
+                        if let Some(sheet_name) = sheet.sheet_name.as_mut() {
+                            sheet_name.push_str(&Data::reverse_escape_notation(text));
+                        }
+                    } else if is_sheet_name {
                         current_sheet += 1;
                         if current_sheet > 1 {
                             debug!("start a new sheet");
                             // start a new sheet
                             // push the last row and block
                             if let Some(id_idx) = mapping.get_auto_increment_idx(current_block) {
-                                row.columns[*id_idx] = format!("{}", current_row);
+                                row.columns[*id_idx] = rule.general.format_id(current_row);
                             }
                             if let Some(title) = mapping.get_title(current_block) {
                                 block.title = title;
@@ -177,17 +300,22 @@ impl Data {
                             sheet.blocks.push(block.clone());
                             sheets.push(sheet.clone());
                             // reset variables
-                            current_block = 0;
+                            block_in_sheet = 0;
+                            current_block = block_idx(current_sheet - 1, 0);
                             current_column = 0;
                             current_row = 1;
                             previous_idx = 0;
                             previous_is_list = false;
                             sheet = Sheet::default();
-                            block = Block::default();
+                            block = Block {
+                                rule_idx: current_block,
+                                ..Default::default()
+                            };
                             row = Row::new(current_block, &mapping);
                             start_new_line = false;
                             is_sheet_name = false;
                             block_start = false;
+                            skip_text = false;
                         }
                         sheet.sheet_name = Some(Data::reverse_escape_notation(&text.to_string()));
                         debug!("sheet name pushed: {:?}", sheet.sheet_name);
@@ -198,7 +326,7 @@ impl Data {
                                 debug!("start a new line (Event::Text)");
                                 if let Some(id_idx) = mapping.get_auto_increment_idx(current_block)
                                 {
-                                    row.columns[*id_idx] = format!("{}", current_row);
+                                    row.columns[*id_idx] = rule.general.format_id(current_row);
                                 }
                                 block.rows.push(row.clone());
                                 row = Row::new(current_block, &mapping);
@@ -206,32 +334,42 @@ impl Data {
                                 previous_idx = 0;
                                 current_row += 1;
                             }
-                            row.columns[*column_idx] = Data::concat(
-                                &row.columns.get(*column_idx),
+                            row.push_text(
+                                *column_idx,
                                 &Data::reverse_escape_notation(
-                                    &text[get_custom_prefix_end_idx()..],
+                                    &text[get_custom_prefix_end_idx(text)..],
                                 ),
+                                &style,
+                                true,
                             );
                             current_column = *column_idx;
+                            skip_text = false;
                             debug!(
                                 "cell pushed => sheet: {}, block: {}, row: {}, column: {}",
                                 current_sheet, current_block, current_row, current_column
                             );
                         }
                         block_start = false;
-                    } else {
-                        row.columns[current_column] = Data::concat(
-                            &row.columns.get(current_column),
+                    } else if !skip_text {
+                        row.push_text(
+                            current_column,
                             &Data::reverse_escape_notation(&text),
+                            &style,
+                            !previous_is_inline,
                         );
                         debug!(
                             "cell pushed => sheet: {}, block: {}, row: {}, column: {}",
                             current_sheet, current_block, current_row, current_column
                         );
                     }
+                    previous_is_inline = true;
                 }
                 Event::End(tag) => {
+                    previous_is_inline = false;
                     is_sheet_name = false;
+                    if let Tag::List(_) = tag {
+                        lists_mapped.pop();
+                    }
                     // store this tag idx as previous tag idx to be used by next loop
                     if let Some(idx) = mapping.get_idx(current_block, Some(&tag), None) {
                         previous_idx = *idx;
@@ -244,10 +382,11 @@ impl Data {
                     }
                 }
                 Event::Rule => {
+                    previous_is_inline = false;
                     debug!("start a new block");
                     // push the last row and push block to blocks
                     if let Some(id_idx) = mapping.get_auto_increment_idx(current_block) {
-                        row.columns[*id_idx] = format!("{}", current_row);
+                        row.columns[*id_idx] = rule.general.format_id(current_row);
                     }
                     block.rows.push(row.clone());
                     if let Some(title) = mapping.get_title(current_block) {
@@ -255,20 +394,25 @@ impl Data {
                     }
                     sheet.blocks.push(block.clone());
                     // start a new block
-                    block = Block::default();
-                    current_block += 1;
+                    block_in_sheet += 1;
+                    current_block = block_idx(current_sheet.saturating_sub(1), block_in_sheet);
+                    block = Block {
+                        rule_idx: current_block,
+                        ..Default::default()
+                    };
                     current_row = 1;
                     current_column = 0;
                     previous_idx = 0;
                     row = Row::new(current_block, &mapping);
                     block_start = true;
+                    skip_text = false;
                 }
-                _ => {}
+                _ => previous_is_inline = false,
             }
         });
         // push the last row and block
         if let Some(id_idx) = mapping.get_auto_increment_idx(current_block) {
-            row.columns[*id_idx] = format!("{}", current_row);
+            row.columns[*id_idx] = rule.general.format_id(current_row);
         }
         if let Some(title) = mapping.get_title(current_block) {
             block.title = title;
@@ -277,10 +421,13 @@ impl Data {
         sheet.blocks.push(block);
         sheets.push(sheet);
 
+        let violations = constraint::check(&sheets, &rule)?;
         let data = Self {
             sheets,
             rule,
             mapping,
+            warnings,
+            violations,
         };
 
         info!("OK");
@@ -288,138 +435,181 @@ impl Data {
         Ok(data)
     }
 
-    #[cfg(feature = "excel")]
-    pub fn export_excel(&self, file_name: &str) -> Result<()> {
-        info!("exporting excel file ({}.xlsx)...", file_name);
-        // TODO: customizable start positions
-        let workbook = Workbook::new(&format!("{}.xlsx", file_name));
-        let title_format = workbook.add_format().set_font_size(16.0).set_bold();
-        let head_row_format = workbook
-            .add_format()
-            .set_text_wrap()
-            .set_align(FormatAlignment::CenterAcross)
-            .set_align(FormatAlignment::VerticalCenter)
-            .set_border(FormatBorder::Thin)
-            .set_bg_color(FormatColor::Cyan);
-        let data_row_format = workbook
-            .add_format()
-            .set_text_wrap()
-            .set_align(FormatAlignment::Left)
-            .set_align(FormatAlignment::VerticalTop)
-            .set_border(FormatBorder::Thin);
-        for sheet in self.sheets.iter() {
-            let (_start_x, _start_y) = (0, 0);
-            let (block_start_x, mut block_start_y) = (0, 0);
-            let mut s = workbook.add_worksheet(sheet.sheet_name.as_deref())?;
-            for (idx, block) in sheet.blocks.iter().enumerate() {
-                // render the block title
-                s.write_string(
-                    block_start_y,
-                    block_start_x,
-                    &block.title,
-                    Some(&title_format),
-                )?;
-                block_start_y += 1;
-                let mut merged_positions: Vec<CellRange> = vec![];
-                if let Some(b) = self.rule.doc.blocks.get(idx) {
-                    // Header
-                    // render the merged cells first
-                    // and store the merged column indexes
-                    for merge_info in b.merge_info.iter() {
-                        s.merge_range(
-                            block_start_y,
-                            merge_info.from,
-                            block_start_y,
-                            merge_info.to,
-                            &merge_info.title,
-                            Some(&head_row_format),
-                        )?;
-                        debug!("(header)merge_range -> start_y: {:?}, start_x: {:?}, end_y: {:?}, end_x: {:?}, text: {:?}", block_start_y, merge_info.from, block_start_y, merge_info.to, &merge_info.title);
-                        merged_positions.push(CellRange::new(merge_info.from, merge_info.to));
-                    }
-                    debug!("merged_positions: {:?}", merged_positions);
-                    // render the remaining headers
-                    let header_merged = !merged_positions.is_empty();
-                    for (pos_x, column) in b.columns.iter().enumerate() {
-                        let pos_x = pos_x as u16;
-                        // check if pos_x is within merged range
-                        let mut in_merged_range = false;
-                        for merged_pos in merged_positions.iter() {
-                            if merged_pos.contain(pos_x) {
-                                in_merged_range = true;
-                                break;
-                            }
-                        }
-                        if in_merged_range {
-                            s.write_string(
-                                block_start_y + 1,
-                                pos_x,
-                                &column.title,
-                                Some(&head_row_format),
-                            )?;
-                            debug!(
-                                "(header)write_string -> y: {:?}, x: {:?}, text: {:?}",
-                                block_start_y + 1,
-                                pos_x,
-                                &column.title
-                            );
-                        } else if header_merged {
-                            s.merge_range(
-                                block_start_y,
-                                pos_x,
-                                block_start_y + 1,
-                                pos_x,
-                                &column.title,
-                                Some(&head_row_format),
-                            )?;
-                            debug!("(header)merge_range -> start_y: {:?}, start_x: {:?}, end_y: {:?}, end_x: {:?}, text: {:?}", block_start_y, pos_x, block_start_y + 1, pos_x, &column.title);
-                        } else {
-                            s.write_string(
-                                block_start_y,
-                                pos_x,
-                                &column.title,
-                                Some(&head_row_format),
-                            )?;
-                            debug!(
-                                "(header)write_string -> y: {:?}, x: {:?}, text: {:?}",
-                                block_start_y, pos_x, &column.title
-                            );
-                        }
-                    }
-                    if header_merged {
-                        block_start_y += 1;
-                    }
-
-                    // Body
-                    let _body_start_x = block_start_x;
-                    let body_start_y = block_start_y + 1;
-                    let mut last_y = 0;
-                    for (y_offset, row) in block.rows.iter().enumerate() {
-                        for (x_offset, column) in row.columns.iter().enumerate() {
-                            s.write_string(
-                                body_start_y + (y_offset as u32),
-                                block_start_x + x_offset as u16,
-                                &column,
-                                Some(&data_row_format),
-                            )?;
-                            debug!(
-                                "(content)write_string -> y: {:?}, x: {:?}, text: {:?}",
-                                body_start_y + (y_offset as u32),
-                                block_start_x + x_offset as u16,
-                                &column
-                            );
-                        }
-                        last_y = y_offset;
+    /// Checks if every heading, list item and custom prefix line in the filtered input
+    /// can be placed in a column and every block has its definition in the rule,
+    /// and handles the ones which can't by `general.unmapped` in the rule.
+    /// Returns the warnings to be reported.
+    fn check(
+        input: &str,
+        source_map: &SourceMap<'_>,
+        rule: &Rule,
+        mapping: &Mapping,
+        selected: &[Vec<usize>],
+        options: Options,
+    ) -> Result<Vec<ParseError>> {
+        // index of the block in the rule selected by `Rule::select_blocks`
+        let block_idx = |sheet_idx: Option<usize>, idx: usize| {
+            sheet_idx
+                .and_then(|sheet_idx| selected.get(sheet_idx))
+                .and_then(|blocks| blocks.get(idx))
+                .copied()
+                .unwrap_or(idx)
+        };
+        // (byte offset in the input, message, can be put into the overflow column?)
+        let mut issues = vec![];
+        // headings and list items
+        let mut current_sheet = None;
+        let mut block_in_sheet = 0;
+        let mut current_block = block_idx(current_sheet, 0);
+        // is each nested list mapped to any column?
+        let mut lists_mapped = vec![];
+        // the separator of the block not defined in the rule, which is reported if it has any content
+        let mut undefined_block = None;
+        let rewritten_prefix = get_custom_prefix_as_normal_list("");
+        for (event, range) in Parser::new_ext(input, options).into_offset_iter() {
+            match &event {
+                Event::Start(Tag::Heading(1)) | Event::Rule => {}
+                _ => {
+                    if let Some((offset, message)) = undefined_block.take() {
+                        issues.push((offset, message, false));
                     }
-
-                    // update block_start_y for the next block
-                    block_start_y += (last_y + 3) as u32;
                 }
             }
+            let notation = match &event {
+                Event::Start(Tag::Heading(1)) => {
+                    current_sheet = Some(current_sheet.map_or(0, |sheet| sheet + 1));
+                    block_in_sheet = 0;
+                    current_block = block_idx(current_sheet, block_in_sheet);
+                    undefined_block = None;
+                    None
+                }
+                Event::Rule => {
+                    block_in_sheet += 1;
+                    current_block = block_idx(current_sheet, block_in_sheet);
+                    undefined_block = (current_block >= rule.doc.blocks.len()).then(|| {
+                        (
+                            range.start,
+                            format!("block {} is not defined in the rule.", block_in_sheet + 1),
+                        )
+                    });
+                    None
+                }
+                Event::Start(Tag::Heading(level)) => Some(format!("heading level {}", level))
+                    .filter(|_| {
+                        mapping
+                            .get_idx(current_block, Some(&Tag::Heading(*level)), None)
+                            .is_none()
+                    }),
+                Event::Text(text) if text.starts_with("####### ") => {
+                    Some(String::from("heading level 7")).filter(|_| {
+                        mapping
+                            .get_idx(current_block, Some(&Tag::Heading(7)), None)
+                            .is_none()
+                    })
+                }
+                Event::Text(text) if text.starts_with("######## ") => {
+                    Some(String::from("heading level 8")).filter(|_| {
+                        mapping
+                            .get_idx(current_block, Some(&Tag::Heading(8)), None)
+                            .is_none()
+                    })
+                }
+                Event::Start(tag @ Tag::List(_)) => {
+                    lists_mapped.push(mapping.get_idx(current_block, Some(tag), None).is_some());
+                    None
+                }
+                Event::End(Tag::List(_)) => {
+                    lists_mapped.pop();
+                    None
+                }
+                // items of custom prefix lists are mapped by their prefixes
+                Event::Start(Tag::Item)
+                    if lists_mapped.last() == Some(&false)
+                        && !input[range.start..].starts_with(&rewritten_prefix) =>
+                {
+                    Some(String::from("list item"))
+                }
+                _ => None,
+            };
+            // the content of the blocks not defined in the rule is reported as the whole block
+            if let (Some(notation), Some(block)) = (notation, rule.doc.blocks.get(current_block)) {
+                issues.push((
+                    range.start,
+                    format!(
+                        "{} is not mapped to any column in block '{}'.",
+                        notation, block.title
+                    ),
+                    true,
+                ));
+            }
         }
-        workbook.close()?;
-        info!("OK");
-        Ok(())
+        // custom prefix lines which are not converted by the filter
+        let prefixes: Vec<&str> = rule
+            .doc
+            .blocks
+            .iter()
+            .flat_map(|block| block.columns.iter())
+            .filter_map(|column| column.custom_prefix.as_deref())
+            .collect();
+        let mut current_sheet = None;
+        let mut block_in_sheet = 0;
+        let mut current_block = block_idx(current_sheet, 0);
+        let mut in_code_block = false;
+        let mut line_start = 0;
+        for line in input.split('\n') {
+            let offset = line_start + line.len() - line.trim_start().len();
+            line_start += line.len() + 1;
+            if is_code_fence(line) {
+                in_code_block = !in_code_block;
+            } else if in_code_block {
+                continue;
+            } else if line.starts_with("# ") {
+                current_sheet = Some(current_sheet.map_or(0, |sheet| sheet + 1));
+                block_in_sheet = 0;
+                current_block = block_idx(current_sheet, block_in_sheet);
+            } else if line.starts_with("---") {
+                block_in_sheet += 1;
+                current_block = block_idx(current_sheet, block_in_sheet);
+            } else if let Some(block) = rule.doc.blocks.get(current_block) {
+                let trimmed = line.trim();
+                let prefix = prefixes.iter().find(|prefix| {
+                    trimmed
+                        .strip_prefix(**prefix)
+                        .is_some_and(|stripped| stripped.starts_with(' '))
+                        && !block
+                            .columns
+                            .iter()
+                            .any(|column| column.custom_prefix.as_deref() == Some(**prefix))
+                });
+                if let Some(prefix) = prefix {
+                    issues.push((
+                        offset,
+                        format!(
+                            "custom prefix '{}' is not defined in block '{}'.",
+                            prefix, block.title
+                        ),
+                        false,
+                    ));
+                }
+            }
+        }
+        issues.sort_by_key(|(offset, _, _)| *offset);
+
+        let mut warnings = vec![];
+        for (offset, message, overflowable) in issues.into_iter() {
+            let line_start = input[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+            let error = source_map.error_at(
+                input[..offset].matches('\n').count(),
+                &Data::reverse_escape_notation(&input[line_start..offset]),
+                &message,
+            );
+            match rule.general.unmapped {
+                Unmapped::Error => return Err(error.into()),
+                Unmapped::Overflow if overflowable => {}
+                _ => warnings.push(error),
+            }
+        }
+        Ok(warnings)
     }
 
     fn concat(target: &Option<&String>, input: &str) -> String {
@@ -431,19 +621,39 @@ impl Data {
         input.to_string()
     }
 
+    /// Escapes `*` except at the beginning of lines (list markers) and around `*italic*` and `**bold**`,
+    /// so that the other asterisks are kept as literal characters.
     fn escape_notation(input: &str) -> String {
         let mut result: String = "".to_string();
         // split \n and convert by one line
         let splits: Vec<&str> = input.split('\n').collect();
         for split in splits {
+            result.push('\n');
             // skip first of line
-            let mut split = split.to_string();
-            let mut head: String = "".to_string();
-            if !split.is_empty() {
-                head = split.remove(0).to_string();
+            let mut chars = split.chars();
+            if let Some(head) = chars.next() {
+                result.push(head);
+            }
+            let mut pos = split.len() - chars.as_str().len();
+            let mut search = 0;
+            while let Some(found) = EMPHASIS.find_at(split, search) {
+                // not emphasis in a word like `a*b*c`, as `_` is
+                let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric());
+                if is_word(split[..found.start()].chars().next_back())
+                    || is_word(split[found.end()..].chars().next())
+                {
+                    search = found.start() + 1;
+                    continue;
+                }
+                if found.start() >= pos {
+                    result.push_str(&split[pos..found.start()].replace("*", "--asterisk--"));
+                    pos = found.start();
+                }
+                result.push_str(&split[pos..found.end()]);
+                pos = found.end();
+                search = found.end();
             }
-            let replaced = split.replace("*", "--asterisk--");
-            result.push_str(&format!("\n{}{}", head, replaced));
+            result.push_str(&split[pos..].replace("*", "--asterisk--"));
         }
         result
     }
@@ -453,12 +663,25 @@ impl Data {
     }
 }
 
-#[derive(Debug, PartialEq, Clone)]
-struct Sheet {
+/// A sheet of the document, started by `# <sheet name>` in the input.
+#[derive(Debug, PartialEq, Clone, Serialize)]
+pub struct Sheet {
     sheet_name: Option<String>,
     blocks: Vec<Block>,
 }
 
+impl Sheet {
+    /// Returns the sheet name.
+    pub fn name(&self) -> Option<&str> {
+        self.sheet_name.as_deref()
+    }
+
+    /// Returns the blocks separated by `---` in this sheet.
+    pub fn blocks(&self) -> &[Block] {
+        &self.blocks
+    }
+}
+
 impl Default for Sheet {
     fn default() -> Self {
         Self {
@@ -468,37 +691,185 @@ impl Default for Sheet {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
-struct Block {
+/// A table in a sheet. Its columns are defined by the block in the rule.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Block {
     title: String,
+    /// index of the block in the rule
+    #[serde(skip)]
+    rule_idx: usize,
     rows: Vec<Row>,
 }
 
+impl Block {
+    /// Returns the block title defined in the rule.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// Returns the index of the block in the rule selected by the block marker or the position.
+    /// It can be passed to `Data::columns`.
+    pub fn rule_idx(&self) -> usize {
+        self.rule_idx
+    }
+
+    /// Returns the rows of this block.
+    pub fn rows(&self) -> &[Row] {
+        &self.rows
+    }
+}
+
 impl Default for Block {
     fn default() -> Self {
         Self {
             title: String::default(),
+            rule_idx: 0,
             rows: vec![],
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
-struct Row {
+/// A row of a block. Cells are stored in the same order as the columns in the rule.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Row {
     columns: Vec<String>,
+    /// Spans of the cells which have inline formatting, keyed by the column index.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    rich_text: BTreeMap<usize, Vec<Span>>,
 }
 
 impl Row {
     fn new(block_idx: usize, mapping: &Mapping) -> Self {
         Row {
             columns: vec![String::default(); mapping.get_size(block_idx).unwrap_or(0)],
+            ..Default::default()
         }
     }
+
+    /// Returns the cells of this row as plain text.
+    pub fn cells(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// Returns the spans of the `idx`-th cell.
+    /// `None` means the cell has no inline formatting and `cells` is enough to render it.
+    pub fn spans(&self, idx: usize) -> Option<&[Span]> {
+        self.rich_text.get(&idx).map(|spans| spans.as_slice())
+    }
+
+    /// Appends the text to the `idx`-th cell.
+    /// If `new_line` is true, the text starts a new line in the cell.
+    fn push_text(&mut self, idx: usize, text: &str, style: &Style, new_line: bool) {
+        let current = match self.columns.get(idx) {
+            Some(current) => current.clone(),
+            None => return,
+        };
+        let cell = if new_line {
+            Data::concat(&Some(&current), text)
+        } else {
+            format!("{}{}", current, text)
+        };
+        // spans are kept only for the cells with inline formatting
+        if !style.is_plain() || self.rich_text.contains_key(&idx) {
+            let spans = self.rich_text.entry(idx).or_insert_with(|| {
+                if current.is_empty() {
+                    vec![]
+                } else {
+                    vec![Span::new(&current, Style::default())]
+                }
+            });
+            let piece = &cell[current.len()..];
+            match spans.last_mut() {
+                Some(last) if &last.style == style => last.text.push_str(piece),
+                _ if piece.is_empty() => {}
+                _ => spans.push(Span::new(piece, style.clone())),
+            }
+        }
+        self.columns[idx] = cell;
+    }
+
+    /// Returns the cell of the column titled `title`.
+    pub fn get(&self, columns: &[rule::Column], title: &str) -> Option<&str> {
+        columns
+            .iter()
+            .position(|column| column.title == title)
+            .and_then(|idx| self.columns.get(idx))
+            .map(|cell| cell.as_str())
+    }
+
+    /// Returns the auto incremented id of this row, if the rule has `isNum` column.
+    pub fn id(&self, columns: &[rule::Column]) -> Option<&str> {
+        columns
+            .iter()
+            .position(|column| column.auto_increment)
+            .and_then(|idx| self.columns.get(idx))
+            .map(|cell| cell.as_str())
+    }
+
+    /// Returns the cells keyed by the column titles.
+    pub fn to_map<'a>(&'a self, columns: &'a [rule::Column]) -> HashMap<&'a str, &'a str> {
+        columns
+            .iter()
+            .zip(self.columns.iter())
+            .map(|(column, cell)| (column.title.as_str(), cell.as_str()))
+            .collect()
+    }
 }
 
 impl Default for Row {
     fn default() -> Self {
-        Self { columns: vec![] }
+        Self {
+            columns: vec![],
+            rich_text: BTreeMap::new(),
+        }
+    }
+}
+
+/// A piece of text in a cell sharing the same inline formatting.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Span {
+    text: String,
+    style: Style,
+}
+
+impl Span {
+    fn new(text: &str, style: Style) -> Self {
+        Span {
+            text: text.to_string(),
+            style,
+        }
+    }
+
+    /// Returns the text of this span.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the inline formatting of this span.
+    pub fn style(&self) -> &Style {
+        &self.style
+    }
+}
+
+/// Inline formatting written in Markdown.
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct Style {
+    /// `__text__` or `**text**`
+    pub bold: bool,
+    /// `_text_` or `*text*`
+    pub italic: bool,
+    /// `~~text~~`
+    pub strikethrough: bool,
+    /// `` `text` ``
+    pub code: bool,
+    /// `[text](url)`
+    pub link: Option<String>,
+}
+
+impl Style {
+    /// Returns true if no formatting is applied.
+    pub fn is_plain(&self) -> bool {
+        *self == Style::default()
     }
 }
 
@@ -508,15 +879,6 @@ mod tests {
 
     use super::*;
 
-    #[test]
-    fn test_cell_range_contain() {
-        let cell_range = CellRange::new(0, 10);
-        assert!(cell_range.contain(0));
-        assert!(cell_range.contain(10));
-        assert!(cell_range.contain(5));
-        assert!(!cell_range.contain(11));
-    }
-
     #[test]
     fn test_default_data() {
         let data = Data::default();
@@ -524,6 +886,7 @@ mod tests {
             sheets: vec![],
             rule: Rule::default(),
             mapping: Mapping::default(),
+            ..Default::default()
         };
         assert_eq!(expected, data);
     }
@@ -540,6 +903,334 @@ mod tests {
             rule,
         );
         assert!(data.is_err());
+        let error = data.unwrap_err().downcast::<ParseError>().unwrap();
+        assert_eq!((1, 1), (error.line, error.column));
+        assert_eq!("## Test Variation 1", error.snippet);
+    }
+
+    #[test]
+    fn test_marshal_error_unmapped_heading() {
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/undefined_prefix.yml").unwrap()).unwrap();
+        let error = Data::marshal(
+            &read_to_string("test_case/input/error_unmapped_heading.md").unwrap(),
+            rule,
+        )
+        .unwrap_err()
+        .downcast::<ParseError>()
+        .unwrap();
+        assert_eq!(
+            ParseError {
+                file: None,
+                line: 7,
+                column: 1,
+                message: String::from(
+                    "heading level 3 is not mapped to any column in block 'Block Title 2'."
+                ),
+                snippet: String::from("### Item 2-1"),
+            },
+            error
+        );
+    }
+
+    #[test]
+    fn test_marshal_error_undefined_prefix() {
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/undefined_prefix.yml").unwrap()).unwrap();
+        let error = Data::marshal(
+            &read_to_string("test_case/input/error_undefined_prefix.md").unwrap(),
+            rule,
+        )
+        .unwrap_err()
+        .downcast::<ParseError>()
+        .unwrap();
+        assert_eq!(
+            ParseError {
+                file: None,
+                line: 7,
+                column: 3,
+                message: String::from("custom prefix '+' is not defined in block 'Block Title 2'."),
+                snippet: String::from("  + Procedure 2"),
+            },
+            error
+        );
+    }
+
+    #[test]
+    fn test_marshal_unmapped_overflow() {
+        let rule = Rule::marshal(&read_to_string("test_case/rule/overflow.yml").unwrap()).unwrap();
+        let data = Data::marshal(
+            &read_to_string("test_case/input/unmapped.md").unwrap(),
+            rule,
+        )
+        .unwrap();
+        let blocks = data.sheets()[0].blocks();
+        assert_eq!(
+            vec![
+                vec![
+                    "1",
+                    "Test Variation 1",
+                    "Test Description",
+                    "Test Variation 1-1"
+                ],
+                vec!["2", "Test Variation 2", "Test Description 2", ""],
+            ],
+            blocks[0]
+                .rows()
+                .iter()
+                .map(|row| row.cells().to_vec())
+                .collect::<Vec<Vec<String>>>()
+        );
+        assert_eq!(
+            vec!["1", "Item 1", "unmapped list\nmore lines"],
+            blocks[1].rows()[0].cells().to_vec()
+        );
+        assert!(data.warnings().is_empty());
+    }
+
+    #[test]
+    fn test_marshal_unmapped_warning() {
+        let mut rule =
+            Rule::marshal(&read_to_string("test_case/rule/overflow.yml").unwrap()).unwrap();
+        rule.general.unmapped = Unmapped::Warning;
+        let data = Data::marshal(
+            &read_to_string("test_case/input/unmapped.md").unwrap(),
+            rule,
+        )
+        .unwrap();
+        let blocks = data.sheets()[0].blocks();
+        assert_eq!(
+            vec!["1", "Test Variation 1", "Test Description", ""],
+            blocks[0].rows()[0].cells().to_vec()
+        );
+        assert_eq!(
+            vec!["1", "Item 1", ""],
+            blocks[1].rows()[0].cells().to_vec()
+        );
+        assert_eq!(
+            vec![
+                (
+                    3,
+                    "heading level 3 is not mapped to any column in block 'Block Title'."
+                ),
+                (
+                    9,
+                    "list item is not mapped to any column in block 'Block Title 2'."
+                ),
+            ],
+            data.warnings()
+                .iter()
+                .map(|warning| (warning.line, warning.message.as_str()))
+                .collect::<Vec<(usize, &str)>>()
+        );
+    }
+
+    #[test]
+    fn test_marshal_constraint() {
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/constraint.yml").unwrap()).unwrap();
+        let data = Data::marshal(
+            &read_to_string("test_case/input/constraint.md").unwrap(),
+            rule,
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                (
+                    2,
+                    "Test Case",
+                    "column 'Test Case' must be at most 20 characters, but has 34."
+                ),
+                (
+                    2,
+                    "Priority",
+                    "column 'Priority' must be one of High, Middle, Low, but got \"Urgent\""
+                ),
+                (
+                    2,
+                    "Expected Result",
+                    "column 'Expected Result' is required but empty."
+                ),
+                (
+                    2,
+                    "Ticket",
+                    "column 'Ticket' does not match the pattern '[A-Z]+-[0-9]+': \"#34\""
+                ),
+            ],
+            data.violations()
+                .iter()
+                .map(|v| (v.row, v.column.as_str(), v.message.as_str()))
+                .collect::<Vec<(usize, &str, &str)>>()
+        );
+        assert!(data
+            .violations()
+            .iter()
+            .all(|v| v.sheet == "Login" && v.block == "Test Cases"));
+    }
+
+    #[test]
+    fn test_marshal_named_block() {
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/named_block.yml").unwrap()).unwrap();
+        let data = Data::marshal(
+            &read_to_string("test_case/input/named_block.md").unwrap(),
+            rule,
+        )
+        .unwrap();
+        let blocks = |sheet: &Sheet| {
+            sheet
+                .blocks()
+                .iter()
+                .map(|block| {
+                    (
+                        block.rule_idx(),
+                        block.title().to_string(),
+                        block
+                            .rows()
+                            .iter()
+                            .map(|row| row.cells().to_vec())
+                            .collect::<Vec<Vec<String>>>(),
+                    )
+                })
+                .collect::<Vec<(usize, String, Vec<Vec<String>>)>>()
+        };
+        let cells = |cells: &[&str]| cells.iter().map(|c| c.to_string()).collect::<Vec<String>>();
+        assert_eq!(
+            vec![
+                (
+                    1,
+                    String::from("Test Cases"),
+                    vec![cells(&[
+                        "1",
+                        "Valid password",
+                        "Input the password",
+                        "Logged in"
+                    ])]
+                ),
+                (
+                    0,
+                    String::from("Preconditions"),
+                    vec![cells(&["1", "Database is running"])]
+                ),
+            ],
+            blocks(&data.sheets()[0])
+        );
+        // blocks without the marker follow the previous one
+        assert_eq!(
+            vec![
+                (
+                    0,
+                    String::from("Preconditions"),
+                    vec![cells(&["1", "User is logged in"])]
+                ),
+                (
+                    1,
+                    String::from("Test Cases"),
+                    vec![cells(&["1", "Logout", "Click logout", "Logged out"])]
+                ),
+            ],
+            blocks(&data.sheets()[1])
+        );
+    }
+
+    #[test]
+    fn test_marshal_code_block() {
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/named_block.yml").unwrap()).unwrap();
+        let data = Data::marshal(
+            "# Sheet\n* Condition\n```\n---\n# Code\n+ code\n```\n---\n## Case\n+ Step",
+            rule,
+        )
+        .unwrap();
+        let blocks = data.sheets()[0].blocks();
+        assert_eq!(
+            vec![(0, "Condition\n---\n# Code\n+ code\n"), (1, "Step")],
+            vec![
+                (
+                    blocks[0].rule_idx(),
+                    blocks[0].rows()[0].cells()[1].as_str()
+                ),
+                (
+                    blocks[1].rule_idx(),
+                    blocks[1].rows()[0].cells()[2].as_str()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_marshal_error_unknown_block() {
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/named_block.yml").unwrap()).unwrap();
+        let error = Data::marshal("# Sheet\n* Condition\n--- Postconditions\n* Done", rule)
+            .unwrap_err()
+            .downcast::<ParseError>()
+            .unwrap();
+        // the name in the marker is pointed
+        assert_eq!((3, 5), (error.line, error.column));
+        assert_eq!(
+            "block 'Postconditions' is not defined in the rule (expected one of: pre, Test Cases).",
+            error.message
+        );
+    }
+
+    #[test]
+    fn test_marshal_undefined_block() {
+        let mut rule =
+            Rule::marshal(&read_to_string("test_case/rule/named_block.yml").unwrap()).unwrap();
+        let input = "# S\n* a\n---\n## C\n--- \n* b";
+        let error = Data::marshal(input, rule.clone())
+            .unwrap_err()
+            .downcast::<ParseError>()
+            .unwrap();
+        assert_eq!(
+            (5, "block 3 is not defined in the rule.", "--- "),
+            (error.line, error.message.as_str(), error.snippet.as_str())
+        );
+        // the block can't be put into the overflow column
+        for unmapped in [Unmapped::Warning, Unmapped::Overflow].iter() {
+            rule.general.unmapped = *unmapped;
+            let data = Data::marshal(input, rule.clone()).unwrap();
+            assert_eq!(
+                vec![(5, "block 3 is not defined in the rule.")],
+                data.warnings()
+                    .iter()
+                    .map(|warning| (warning.line, warning.message.as_str()))
+                    .collect::<Vec<(usize, &str)>>()
+            );
+        }
+        // the empty block has nothing to be lost
+        rule.general.unmapped = Unmapped::Error;
+        assert!(Data::marshal("# S\n* a\n---\n## C\n---\n\n# T\n* b", rule).is_ok());
+    }
+
+    #[test]
+    fn test_marshal_unmapped_error() {
+        let mut rule =
+            Rule::marshal(&read_to_string("test_case/rule/overflow.yml").unwrap()).unwrap();
+        rule.general.unmapped = Unmapped::Error;
+        let error = Data::marshal(
+            &read_to_string("test_case/input/unmapped.md").unwrap(),
+            rule,
+        )
+        .unwrap_err()
+        .downcast::<ParseError>()
+        .unwrap();
+        assert_eq!(3, error.line);
+    }
+
+    #[test]
+    fn test_marshal_unmapped_error_column() {
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/named_block.yml").unwrap()).unwrap();
+        // the heading in the list item is not at the beginning of the line
+        for (input, column) in [("# S\n* a\n* ## b", 3), ("# S\n* a\n1. ## b", 4)].iter() {
+            let error = Data::marshal(input, rule.clone())
+                .unwrap_err()
+                .downcast::<ParseError>()
+                .unwrap();
+            assert_eq!((3, *column), (error.line, error.column), "{}", error);
+        }
     }
 
     #[test]
@@ -556,6 +1247,7 @@ mod tests {
             sheets: vec![Sheet {
                 sheet_name: Some(String::from("Sheet Name")),
                 blocks: vec![Block {
+                    rule_idx: 0,
                     title: String::from("Block Title"),
                     rows: vec![
                         Row {
@@ -570,6 +1262,7 @@ mod tests {
                                 String::from("Test Variation 1-1-1-1-1-1-1"),
                                 String::from("Test Description\nmore lines..."),
                             ],
+                            ..Default::default()
                         },
                         Row {
                             columns: vec![
@@ -583,6 +1276,7 @@ mod tests {
                                 String::default(),
                                 String::from("Test Description\nmore lines..."),
                             ],
+                            ..Default::default()
                         },
                         Row {
                             columns: vec![
@@ -596,12 +1290,14 @@ mod tests {
                                 String::default(),
                                 String::from("Test Description\nmore lines..."),
                             ],
+                            ..Default::default()
                         },
                     ],
                 }],
             }],
             mapping,
             rule: rule_clone,
+            ..Default::default()
         };
         assert_eq!(expected, data);
     }
@@ -622,6 +1318,7 @@ mod tests {
                 sheet_name: Some(String::from("Sheet Name")),
                 blocks: vec![
                     Block {
+                        rule_idx: 0,
                         title: String::from("Block Title 1"),
                         rows: vec![
                             Row {
@@ -636,6 +1333,7 @@ mod tests {
                                     String::from("Test Variation A 1-1-1-1-1-1-1"),
                                     String::from("Test Description\nmore lines..."),
                                 ],
+                                ..Default::default()
                             },
                             Row {
                                 columns: vec![
@@ -649,6 +1347,7 @@ mod tests {
                                     String::default(),
                                     String::from("Test Description\nmore lines..."),
                                 ],
+                                ..Default::default()
                             },
                             Row {
                                 columns: vec![
@@ -662,10 +1361,12 @@ mod tests {
                                     String::default(),
                                     String::from("Test Description\nmore lines..."),
                                 ],
+                                ..Default::default()
                             },
                         ],
                     },
                     Block {
+                        rule_idx: 1,
                         title: String::from("Block Title 2"),
                         rows: vec![
                             Row {
@@ -680,6 +1381,7 @@ mod tests {
                                     String::from("Test Variation B 1-1-1-1-1-1-1"),
                                     String::from("Test Description\nmore lines..."),
                                 ],
+                                ..Default::default()
                             },
                             Row {
                                 columns: vec![
@@ -693,6 +1395,7 @@ mod tests {
                                     String::default(),
                                     String::from("Test Description\nmore lines..."),
                                 ],
+                                ..Default::default()
                             },
                             Row {
                                 columns: vec![
@@ -706,10 +1409,12 @@ mod tests {
                                     String::default(),
                                     String::from("Test Description\nmore lines..."),
                                 ],
+                                ..Default::default()
                             },
                         ],
                     },
                     Block {
+                        rule_idx: 2,
                         title: String::from("Block Title 3"),
                         rows: vec![
                             Row {
@@ -724,6 +1429,7 @@ mod tests {
                                     String::from("Test Variation C 1-1-1-1-1-1-1"),
                                     String::from("Test Description\nmore lines..."),
                                 ],
+                                ..Default::default()
                             },
                             Row {
                                 columns: vec![
@@ -737,6 +1443,7 @@ mod tests {
                                     String::default(),
                                     String::from("Test Description\nmore lines..."),
                                 ],
+                                ..Default::default()
                             },
                             Row {
                                 columns: vec![
@@ -750,6 +1457,7 @@ mod tests {
                                     String::default(),
                                     String::from("Test Description\nmore lines..."),
                                 ],
+                                ..Default::default()
                             },
                         ],
                     },
@@ -757,6 +1465,7 @@ mod tests {
             }],
             mapping,
             rule: rule_clone,
+            ..Default::default()
         };
         assert_eq!(expected, data);
     }
@@ -777,6 +1486,7 @@ mod tests {
                 sheet_name: Some(String::from("Sheet Name")),
                 blocks: vec![
                     Block {
+                        rule_idx: 0,
                         title: String::from("Block Title 1"),
                         rows: vec![
                             Row {
@@ -793,6 +1503,7 @@ mod tests {
                                     String::from("Procedure A-A\nProcedure A-B\nProcedure A-C"),
                                     String::from("2021/01/01"),
                                 ],
+                                ..Default::default()
                             },
                             Row {
                                 columns: vec![
@@ -808,6 +1519,7 @@ mod tests {
                                     String::from("Procedure B-A\nProcedure B-B"),
                                     String::from("2021/01/01"),
                                 ],
+                                ..Default::default()
                             },
                             Row {
                                 columns: vec![
@@ -823,10 +1535,12 @@ mod tests {
                                     String::from("Procedure"),
                                     String::from("2021/01/02"),
                                 ],
+                                ..Default::default()
                             },
                         ],
                     },
                     Block {
+                        rule_idx: 1,
                         title: String::from("Block Title 2"),
                         rows: vec![
                             Row {
@@ -836,6 +1550,7 @@ mod tests {
                                     String::default(),
                                     String::from("OK"),
                                 ],
+                                ..Default::default()
                             },
                             Row {
                                 columns: vec![
@@ -844,6 +1559,7 @@ mod tests {
                                     String::from("Description\nmore lines..."),
                                     String::from("NG"),
                                 ],
+                                ..Default::default()
                             },
                         ],
                     },
@@ -851,6 +1567,7 @@ mod tests {
             }],
             mapping,
             rule: rule_clone,
+            ..Default::default()
         };
         assert_eq!(expected, data);
     }
@@ -872,6 +1589,7 @@ mod tests {
                     sheet_name: Some(String::from("Sheet Name 1")),
                     blocks: vec![
                         Block {
+                            rule_idx: 0,
                             title: String::from("Block Title 1"),
                             rows: vec![
                                 Row {
@@ -888,6 +1606,7 @@ mod tests {
                                         String::from("Procedure A-A\nProcedure A-B\nProcedure A-C"),
                                         String::from("2021/01/01"),
                                     ],
+                                    ..Default::default()
                                 },
                                 Row {
                                     columns: vec![
@@ -903,6 +1622,7 @@ mod tests {
                                         String::from("Procedure B-A\nProcedure B-B"),
                                         String::from("2021/01/01"),
                                     ],
+                                    ..Default::default()
                                 },
                                 Row {
                                     columns: vec![
@@ -918,10 +1638,12 @@ mod tests {
                                         String::from("Procedure"),
                                         String::from("2021/01/02"),
                                     ],
+                                    ..Default::default()
                                 },
                             ],
                         },
                         Block {
+                            rule_idx: 1,
                             title: String::from("Block Title 2"),
                             rows: vec![
                                 Row {
@@ -931,6 +1653,7 @@ mod tests {
                                         String::default(),
                                         String::from("OK"),
                                     ],
+                                    ..Default::default()
                                 },
                                 Row {
                                     columns: vec![
@@ -939,6 +1662,7 @@ mod tests {
                                         String::from("Description\nmore lines..."),
                                         String::from("NG"),
                                     ],
+                                    ..Default::default()
                                 },
                             ],
                         },
@@ -948,6 +1672,7 @@ mod tests {
                     sheet_name: Some(String::from("Sheet Name 2")),
                     blocks: vec![
                         Block {
+                            rule_idx: 0,
                             title: String::from("Block Title 1"),
                             rows: vec![
                                 Row {
@@ -964,6 +1689,7 @@ mod tests {
                                         String::from("Procedure A-A\nProcedure A-B\nProcedure A-C"),
                                         String::from("2021/01/01"),
                                     ],
+                                    ..Default::default()
                                 },
                                 Row {
                                     columns: vec![
@@ -979,6 +1705,7 @@ mod tests {
                                         String::from("Procedure B-A\nProcedure B-B"),
                                         String::from("2021/01/01"),
                                     ],
+                                    ..Default::default()
                                 },
                                 Row {
                                     columns: vec![
@@ -994,10 +1721,12 @@ mod tests {
                                         String::from("Procedure"),
                                         String::from("2021/01/02"),
                                     ],
+                                    ..Default::default()
                                 },
                             ],
                         },
                         Block {
+                            rule_idx: 1,
                             title: String::from("Block Title 2"),
                             rows: vec![
                                 Row {
@@ -1007,6 +1736,7 @@ mod tests {
                                         String::default(),
                                         String::from("OK"),
                                     ],
+                                    ..Default::default()
                                 },
                                 Row {
                                     columns: vec![
@@ -1015,6 +1745,7 @@ mod tests {
                                         String::from("Description\nmore lines..."),
                                         String::from("NG"),
                                     ],
+                                    ..Default::default()
                                 },
                             ],
                         },
@@ -1023,6 +1754,7 @@ mod tests {
             ],
             mapping,
             rule: rule_clone,
+            ..Default::default()
         };
         assert_eq!(expected, data);
     }
@@ -1042,6 +1774,7 @@ mod tests {
             sheets: vec![Sheet {
                 sheet_name: Some(String::from("Sheet Name")),
                 blocks: vec![Block {
+                    rule_idx: 0,
                     title: String::from("Block Title"),
                     rows: vec![
                         Row {
@@ -1050,6 +1783,7 @@ mod tests {
                                 String::from("Test Variation 1"),
                                 String::from("Test Variation 1-1"),
                             ],
+                            ..Default::default()
                         },
                         Row {
                             columns: vec![
@@ -1057,6 +1791,7 @@ mod tests {
                                 String::default(),
                                 String::from("Test Variation 1-2"),
                             ],
+                            ..Default::default()
                         },
                         Row {
                             columns: vec![
@@ -1064,6 +1799,7 @@ mod tests {
                                 String::from("Test Variation 2"),
                                 String::default(),
                             ],
+                            ..Default::default()
                         },
                         Row {
                             columns: vec![
@@ -1071,6 +1807,7 @@ mod tests {
                                 String::from("Test Variation 3"),
                                 String::from("Test Variation 3-1"),
                             ],
+                            ..Default::default()
                         },
                         Row {
                             columns: vec![
@@ -1078,12 +1815,126 @@ mod tests {
                                 String::default(),
                                 String::from("Test Variation 3-2"),
                             ],
+                            ..Default::default()
                         },
                     ],
                 }],
             }],
             mapping,
             rule: rule_clone,
+            ..Default::default()
+        };
+        assert_eq!(expected, data);
+    }
+
+    #[test]
+    fn test_marshal_ordered_list() {
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/ordered_list.yml").unwrap()).unwrap();
+        let data = Data::marshal(
+            &read_to_string("test_case/input/ordered_list.md").unwrap(),
+            rule,
+        )
+        .unwrap();
+        let rows = data.sheets[0].blocks[0].rows.clone();
+        assert_eq!(
+            vec![
+                Row {
+                    columns: vec![
+                        String::from("1"),
+                        String::from("Test Case 1"),
+                        String::from("Open the file\nSave the file"),
+                        String::from("The file is saved"),
+                    ],
+                    ..Default::default()
+                },
+                Row {
+                    columns: vec![
+                        String::from("2"),
+                        String::from("Test Case 2"),
+                        String::from("Close the file"),
+                        String::from("The file is closed\nwithout any dialog"),
+                    ],
+                    ..Default::default()
+                },
+            ],
+            rows
+        );
+    }
+
+    #[test]
+    fn test_marshal_word_prefix() {
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/word_prefix.yml").unwrap()).unwrap();
+        let data = Data::marshal(
+            &read_to_string("test_case/input/word_prefix.md").unwrap(),
+            rule,
+        )
+        .unwrap();
+        let rows = data.sheets[0].blocks[0].rows.clone();
+        assert_eq!(
+            vec![
+                Row {
+                    columns: vec![
+                        String::from("1"),
+                        String::from("Test Case 1"),
+                        String::from("The file exists"),
+                        String::from("Open the file\nSave the file"),
+                        String::from("The file is saved\nwithout any dialog"),
+                    ],
+                    ..Default::default()
+                },
+                Row {
+                    columns: vec![
+                        String::from("2"),
+                        String::from("Test Case 2"),
+                        String::default(),
+                        String::from("Close the file"),
+                        String::from("The file is closed"),
+                    ],
+                    ..Default::default()
+                },
+            ],
+            rows
+        );
+    }
+
+    #[test]
+    fn test_marshal_general() {
+        let rule = Rule::marshal(&read_to_string("test_case/rule/general.yml").unwrap()).unwrap();
+        let rule_clone = rule.clone();
+        let mapping = Mapping::new(&rule).unwrap();
+        let data =
+            Data::marshal(&read_to_string("test_case/input/general.md").unwrap(), rule).unwrap();
+        let expected = Data {
+            sheets: vec![Sheet {
+                sheet_name: Some(String::from("Sheet Name")),
+                blocks: vec![Block {
+                    rule_idx: 0,
+                    title: String::from("Block Title"),
+                    rows: vec![
+                        Row {
+                            columns: vec![
+                                String::from("IT-001"),
+                                String::from("Test Variation 1"),
+                                String::from("Test Description 1"),
+                            ],
+                            ..Default::default()
+                        },
+                        Row {
+                            columns: vec![
+                                String::from("IT-002"),
+                                String::from("Test Variation 2"),
+                                String::from("Test Description 2"),
+                            ],
+                            ..Default::default()
+                        },
+                    ],
+                }],
+            }],
+            mapping,
+            rule: rule_clone,
+            ..Default::default()
         };
         assert_eq!(expected, data);
     }
@@ -1103,6 +1954,7 @@ mod tests {
                 sheet_name: Some(String::from("Sheet Name")),
                 blocks: vec![
                     Block {
+                        rule_idx: 0,
                         title: String::from("Block Title 1"),
                         rows: vec![
                             Row {
@@ -1111,6 +1963,7 @@ mod tests {
                                     String::from("cell A 1\nmore lines..."),
                                     String::from("cell B 1\nmore lines..."),
                                 ],
+                                ..Default::default()
                             },
                             Row {
                                 columns: vec![
@@ -1118,6 +1971,7 @@ mod tests {
                                     String::from("cell A 2"),
                                     String::from("cell B 2"),
                                 ],
+                                ..Default::default()
                             },
                             Row {
                                 columns: vec![
@@ -1125,10 +1979,12 @@ mod tests {
                                     String::from("cell A 3\nmore lines..."),
                                     String::default(),
                                 ],
+                                ..Default::default()
                             },
                         ],
                     },
                     Block {
+                        rule_idx: 1,
                         title: String::from("Block Title 2"),
                         rows: vec![
                             Row {
@@ -1137,6 +1993,7 @@ mod tests {
                                     String::from("another cell A 1\nmore lines..."),
                                     String::from("another cell B 1\nmore lines...\nmore lines..."),
                                 ],
+                                ..Default::default()
                             },
                             Row {
                                 columns: vec![
@@ -1144,6 +2001,7 @@ mod tests {
                                     String::from("another cell A 2"),
                                     String::default(),
                                 ],
+                                ..Default::default()
                             },
                         ],
                     },
@@ -1151,6 +2009,7 @@ mod tests {
             }],
             mapping,
             rule: rule_clone,
+            ..Default::default()
         };
         assert_eq!(expected, data);
     }
@@ -1169,6 +2028,7 @@ mod tests {
             sheets: vec![Sheet {
                 sheet_name: Some(String::from("Sheet Name")),
                 blocks: vec![Block {
+                    rule_idx: 0,
                     title: String::from("Block Title"),
                     rows: vec![Row {
                         columns: vec![
@@ -1184,26 +2044,164 @@ mod tests {
                                 "Test Description_astarisk\nsingle *\ndouble **\nwith space * * *",
                             ),
                         ],
+                        ..Default::default()
                     }],
                 }],
             }],
             mapping,
             rule: rule_clone,
+            ..Default::default()
         };
         assert_eq!(expected, data);
     }
 
     #[test]
-    fn test_export_excel() {
-        let rule = get_default_rule();
+    fn test_marshal_inline_format() {
         let data = Data::marshal(
-            &read_to_string("test_case/input/single_block_multi_row.md").unwrap(),
+            &read_to_string("test_case/input/inline_format.md").unwrap(),
+            get_default_rule(),
+        )
+        .unwrap();
+        let row = &data.sheets()[0].blocks()[0].rows()[0];
+        let code = Style {
+            code: true,
+            ..Default::default()
+        };
+        assert_eq!("Test Variation 1", row.cells()[1]);
+        assert_eq!(
+            Some(
+                &[
+                    Span::new("Test ", Style::default()),
+                    Span::new("Variation", code.clone()),
+                    Span::new(" 1", Style::default()),
+                ][..]
+            ),
+            row.spans(1)
+        );
+        // cells without inline formatting have no spans
+        assert_eq!(None, row.spans(2));
+        assert_eq!(
+            "Open config.yml and set bold italic strike\nsee the docs\ncode only line",
+            row.cells()[8]
+        );
+        assert_eq!(
+            Some(
+                &[
+                    Span::new("Open ", Style::default()),
+                    Span::new("config.yml", code.clone()),
+                    Span::new(" and set ", Style::default()),
+                    Span::new(
+                        "bold",
+                        Style {
+                            bold: true,
+                            ..Default::default()
+                        }
+                    ),
+                    Span::new(" ", Style::default()),
+                    Span::new(
+                        "italic",
+                        Style {
+                            italic: true,
+                            ..Default::default()
+                        }
+                    ),
+                    Span::new(" ", Style::default()),
+                    Span::new(
+                        "strike",
+                        Style {
+                            strikethrough: true,
+                            ..Default::default()
+                        }
+                    ),
+                    Span::new("\nsee ", Style::default()),
+                    Span::new(
+                        "the docs",
+                        Style {
+                            link: Some(String::from("https://example.com")),
+                            ..Default::default()
+                        }
+                    ),
+                    Span::new("\ncode only line", code),
+                ][..]
+            ),
+            row.spans(8)
+        );
+        // `*` works in the same way as `_`
+        let data = Data::marshal(
+            "# Sheet\n## Variation\n* plain **bold** and *it*, but a*b*c and 2 * 3 * 4\n* **bold** first",
+            get_default_rule(),
+        )
+        .unwrap();
+        let row = &data.sheets()[0].blocks()[0].rows()[0];
+        assert_eq!(
+            "plain bold and it, but a*b*c and 2 * 3 * 4\nbold first",
+            row.cells()[8]
+        );
+        let bold = Style {
+            bold: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            Some(
+                &[
+                    Span::new("plain ", Style::default()),
+                    Span::new("bold", bold.clone()),
+                    Span::new(" and ", Style::default()),
+                    Span::new(
+                        "it",
+                        Style {
+                            italic: true,
+                            ..Default::default()
+                        }
+                    ),
+                    Span::new(", but a*b*c and 2 * 3 * 4", Style::default()),
+                    Span::new("\nbold", bold),
+                    Span::new(" first", Style::default()),
+                ][..]
+            ),
+            row.spans(8)
+        );
+    }
+
+    #[test]
+    fn test_accessors() {
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/various_list.yml").unwrap()).unwrap();
+        let data = Data::marshal(
+            &read_to_string("test_case/input/various_list_multiple_sheet.md").unwrap(),
             rule,
         )
         .unwrap();
-        let file_name = "unit_test";
-        assert!(data.export_excel("unit_test").is_ok());
-        std::fs::remove_file(format!("{}.xlsx", file_name)).unwrap();
+        assert_eq!(
+            vec![Some("Sheet Name 1"), Some("Sheet Name 2")],
+            data.sheets()
+                .iter()
+                .map(|sheet| sheet.name())
+                .collect::<Vec<Option<&str>>>()
+        );
+        let sheet = &data.sheets()[0];
+        assert_eq!(
+            vec!["Block Title 1", "Block Title 2"],
+            sheet
+                .blocks()
+                .iter()
+                .map(|block| block.title())
+                .collect::<Vec<&str>>()
+        );
+        let columns = data.columns(1);
+        let row = &sheet.blocks()[1].rows()[1];
+        assert_eq!(4, row.cells().len());
+        assert_eq!(Some("2"), row.id(columns));
+        assert_eq!(Some("cell 2"), row.get(columns, "Column 1"));
+        assert_eq!(None, row.get(columns, "Unknown"));
+        let mut expected = HashMap::new();
+        expected.insert("No", "2");
+        expected.insert("Column 1", "cell 2");
+        expected.insert("Description", "Description\nmore lines...");
+        expected.insert("Result", "NG");
+        assert_eq!(expected, row.to_map(columns));
+        assert!(data.columns(99).is_empty());
+        assert_eq!(&data.rule, data.rule());
     }
 
     #[test]
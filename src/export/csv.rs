@@ -0,0 +1,219 @@
+#[cfg(not(test))]
+use log::info;
+
+#[cfg(test)]
+use std::println as info;
+
+use std::{fs, io::Write};
+
+use anyhow::Result;
+
+use crate::{
+    data::{Block, Data},
+    export::Exporter,
+    rule,
+    utils::{sanitize_filename, to_csv_record},
+};
+
+/// `.csv` for each block.
+pub struct CsvExporter;
+
+impl Exporter for CsvExporter {
+    fn name(&self) -> &str {
+        "csv"
+    }
+
+    fn extension(&self) -> &str {
+        "csv"
+    }
+
+    /// Exports every block as its own CSV file named `<file_name>_<sheet name>_<n>_<block title>.csv`,
+    /// where `n` is the 1-based position of the block in the sheet.
+    /// The header row is made from the column titles in the rule,
+    /// and if the block has groups, the group titles are put on the row above it.
+    fn export(&self, data: &Data, file_name: &str) -> Result<()> {
+        let mut files: Vec<(String, String)> = vec![];
+        for (sheet_idx, sheet) in data.sheets().iter().enumerate() {
+            let sheet_name = match sheet.name() {
+                Some(name) => name.to_string(),
+                None => format!("Sheet{}", sheet_idx + 1),
+            };
+            for (block_idx, block) in sheet.blocks().iter().enumerate() {
+                if let Some(b) = data.rule().doc.blocks.get(block.rule_idx()) {
+                    let path = format!(
+                        "{}_{}_{}_{}.csv",
+                        file_name,
+                        sanitize_filename(&sheet_name),
+                        block_idx + 1,
+                        sanitize_filename(block.title())
+                    );
+                    // sheet names can be the same after sanitized
+                    if files.iter().any(|(written, _)| *written == path) {
+                        return Err(anyhow::anyhow!(
+                            "multiple blocks are exported into the same file '{}', rename the sheet '{}'",
+                            path,
+                            sheet_name
+                        ));
+                    }
+                    files.push((path, block_to_csv(b, block)));
+                }
+            }
+        }
+        // nothing is written if the file names conflict
+        for (path, csv) in files.iter() {
+            info!("exporting csv file ({})...", path);
+            fs::write(path, csv)?;
+        }
+        info!("OK");
+        Ok(())
+    }
+
+    /// Only a document with a single block can be written, since a CSV can not hold multiple blocks.
+    fn write(&self, data: &Data, writer: &mut dyn Write) -> Result<()> {
+        writer.write_all(to_single_csv(data)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Returns the CSV of the only block in the document.
+/// A CSV can not hold multiple blocks, so it's an error if the document has other blocks.
+fn to_single_csv(data: &Data) -> Result<String> {
+    let blocks: Vec<(&rule::Block, &Block)> = data
+        .sheets()
+        .iter()
+        .flat_map(|sheet| sheet.blocks().iter())
+        .filter_map(|block| {
+            data.rule()
+                .doc
+                .blocks
+                .get(block.rule_idx())
+                .map(|b| (b, block))
+        })
+        .collect();
+    match blocks.as_slice() {
+        [(b, block)] => Ok(block_to_csv(b, block)),
+        _ => Err(anyhow::anyhow!(
+            "the document has {} blocks, but only one block can be written as a single CSV",
+            blocks.len()
+        )),
+    }
+}
+
+fn block_to_csv(rule_block: &rule::Block, block: &Block) -> String {
+    let mut lines = vec![];
+    // Header
+    if !rule_block.merge_info.is_empty() {
+        let mut group_titles = vec![String::default(); rule_block.columns.len()];
+        for merge_info in rule_block.merge_info.iter() {
+            for title in group_titles
+                .iter_mut()
+                .take(merge_info.to as usize + 1)
+                .skip(merge_info.from as usize)
+            {
+                *title = merge_info.title.clone();
+            }
+        }
+        lines.push(to_csv_record(&group_titles));
+    }
+    lines.push(to_csv_record(
+        &rule_block
+            .columns
+            .iter()
+            .map(|column| column.title.clone())
+            .collect::<Vec<String>>(),
+    ));
+    // Body
+    for row in block.rows().iter() {
+        lines.push(to_csv_record(row.cells()));
+    }
+    lines
+        .into_iter()
+        .map(|line| format!("{}\r\n", line))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use super::*;
+
+    use crate::rule::Rule;
+
+    #[test]
+    fn test_export_csv() {
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/multi_block.yml").unwrap()).unwrap();
+        let data = Data::marshal(
+            &read_to_string("test_case/input/multi_block_multi_row.md").unwrap(),
+            rule,
+        )
+        .unwrap();
+        let file_name = "unit_test_csv";
+        assert!(CsvExporter.export(&data, file_name).is_ok());
+        let path = format!("{}_Sheet_Name_1_Block_Title_1.csv", file_name);
+        let csv = read_to_string(&path).unwrap();
+        let mut lines = csv.split("\r\n");
+        assert_eq!(
+            Some(",Variation,Variation,Variation,Variation,Variation,Variation,Variation,"),
+            lines.next()
+        );
+        assert_eq!(
+            Some("No,Variation 1,Variation 2,Variation 3,Variation 4,Variation 5,Variation 6,Variation 7,Description"),
+            lines.next()
+        );
+        assert_eq!(
+            Some("1,Test Variation A 1,Test Variation A 1-1,Test Variation A 1-1-1,Test Variation A 1-1-1-1,Test Variation A 1-1-1-1-1,Test Variation A 1-1-1-1-1-1,Test Variation A 1-1-1-1-1-1-1,\"Test Description\nmore lines...\""),
+            lines.next()
+        );
+        for idx in 1..=3 {
+            std::fs::remove_file(format!(
+                "{}_Sheet_Name_{}_Block_Title_{}.csv",
+                file_name, idx, idx
+            ))
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_export_csv_same_title() {
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/named_block.yml").unwrap()).unwrap();
+        // the blocks with the same title are written into different files
+        let data = Data::marshal("# S\n* a\n--- pre\n* b", rule.clone()).unwrap();
+        let file_name = "unit_test_csv_same_title";
+        assert!(CsvExporter.export(&data, file_name).is_ok());
+        for (idx, cell) in [(1, "a"), (2, "b")].iter() {
+            let path = format!("{}_S_{}_Preconditions.csv", file_name, idx);
+            assert_eq!(
+                format!("No,Condition\r\n1,{}\r\n", cell),
+                read_to_string(&path).unwrap()
+            );
+            std::fs::remove_file(path).unwrap();
+        }
+        // the sheet names are the same after sanitized
+        let data = Data::marshal("# A/B\n* a\n# A_B\n* b", rule).unwrap();
+        let file_name = "unit_test_csv_same_sheet";
+        assert!(CsvExporter.export(&data, file_name).is_err());
+        assert!(std::fs::metadata(format!("{}_A_B_1_Preconditions.csv", file_name)).is_err());
+    }
+
+    #[test]
+    fn test_to_single_csv() {
+        let rule = Rule::marshal(&read_to_string("test_case/rule/general.yml").unwrap()).unwrap();
+        let data =
+            Data::marshal(&read_to_string("test_case/input/general.md").unwrap(), rule).unwrap();
+        assert_eq!(
+            "No,Variation 1,Description\r\nIT-001,Test Variation 1,Test Description 1\r\nIT-002,Test Variation 2,Test Description 2\r\n",
+            to_single_csv(&data).unwrap()
+        );
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/multi_block.yml").unwrap()).unwrap();
+        let data = Data::marshal(
+            &read_to_string("test_case/input/multi_block_multi_row.md").unwrap(),
+            rule,
+        )
+        .unwrap();
+        assert!(to_single_csv(&data).is_err());
+    }
+}
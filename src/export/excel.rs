@@ -0,0 +1,541 @@
+#[cfg(not(test))]
+use log::{debug, info};
+
+#[cfg(test)]
+use std::{println as info, println as debug};
+
+use anyhow::Result;
+use xlsxwriter::*;
+
+use crate::{
+    data::{Data, Style},
+    export::{
+        layout::{CellKind, SheetLayout, TableLayout},
+        Exporter,
+    },
+    rule,
+    style::{self, CellStyle, Styles},
+    summary::{self, count_keys, summarize},
+    utils::estimate_column_width,
+};
+
+/// `.xlsx` with the styles, the inline formatting and the cover sheet.
+pub struct ExcelExporter;
+
+impl Exporter for ExcelExporter {
+    fn name(&self) -> &str {
+        "xlsx"
+    }
+
+    fn extension(&self) -> &str {
+        "xlsx"
+    }
+
+    /// Warns the autofilters ignored, since excel allows only one autofilter in a sheet.
+    fn warnings(&self, data: &Data) -> Result<Vec<String>> {
+        let mut warnings = vec![];
+        for (idx, sheet) in data.sheets().iter().enumerate() {
+            let sheet_layout = SheetLayout::new(sheet, data.rule())?;
+            let kept = sheet_layout.autofilter_block().unwrap_or_default();
+            for title in sheet_layout.ignored_autofilters.iter() {
+                warnings.push(format!(
+                    "autofilter of block '{}' in sheet '{}' is ignored, since excel allows only one autofilter in a sheet and block '{}' has it.",
+                    title,
+                    summary::sheet_name(sheet, idx),
+                    kept
+                ));
+            }
+        }
+        Ok(warnings)
+    }
+
+    fn export(&self, data: &Data, file_name: &str) -> Result<()> {
+        info!("exporting excel file ({}.xlsx)...", file_name);
+        let workbook = Workbook::new(&format!("{}.xlsx", file_name));
+        // the document level styles applied to every block
+        let doc_styles = Styles::builtin().merge(&data.rule().style);
+        if let Some(cover) = &data.rule().cover {
+            export_cover(data, &workbook, cover, &doc_styles)?;
+        }
+        for (idx, sheet) in data.sheets().iter().enumerate() {
+            let sheet_layout = SheetLayout::new(sheet, data.rule())?;
+            // named explicitly, since the default names are numbered after the cover sheet
+            let mut s = workbook.add_worksheet(Some(&summary::sheet_name(sheet, idx)))?;
+            // stamp the copyright into the footer
+            // `&` is the control character of the header/footer, so it should be escaped
+            if let Some(copyright) = &data.rule().general.copyright {
+                s.set_footer(&format!("&C{}", copyright.replace("&", "&&")))?;
+            }
+            for block in sheet_layout.blocks.iter() {
+                let title_format = add_style_format(workbook.add_format(), &block.title_style);
+                // render the block title
+                let (title_y, title_x) = block.title_pos;
+                s.write_string(title_y, title_x, block.title, Some(&title_format))?;
+                if let Some(table) = &block.table {
+                    write_table(&workbook, &mut s, table)?;
+                }
+            }
+            for (x, width) in sheet_layout.column_widths.iter() {
+                s.set_column(*x, *x, *width, None)?;
+            }
+            for (y, height) in sheet_layout.row_heights.iter() {
+                s.set_row(*y, *height, None)?;
+            }
+            if let Some(row) = sheet_layout.freeze_row {
+                s.freeze_panes(row, 0);
+            }
+        }
+        workbook.close()?;
+        info!("OK");
+        Ok(())
+    }
+}
+
+/// Renders the header and the body of the block placed by `SheetLayout`.
+fn write_table(workbook: &Workbook, s: &mut Worksheet<'_>, table: &TableLayout<'_>) -> Result<()> {
+    let column_styles = &table.column_styles;
+    let head_row_formats: Vec<Format<'_>> = column_styles
+        .iter()
+        .map(|styles| add_style_format(workbook.add_format(), &styles.header))
+        .collect();
+    let data_row_formats: Vec<Format<'_>> = column_styles
+        .iter()
+        .map(|styles| add_style_format(workbook.add_format(), &styles.body))
+        .collect();
+    let highlight_formats: Vec<Format<'_>> = column_styles
+        .iter()
+        .map(|styles| add_highlight_format(add_style_format(workbook.add_format(), &styles.body)))
+        .collect();
+    // the value styles are matched by `SheetLayout` and written as the static formats of the cells,
+    // since the xlsxwriter crate doesn't expose the conditional formats of libxlsxwriter
+    let value_formats: Vec<Vec<Format<'_>>> = table
+        .value_styles
+        .iter()
+        .map(|styles| {
+            styles
+                .iter()
+                .map(|style| add_style_format(workbook.add_format(), style))
+                .collect()
+        })
+        .collect();
+    let group_format = add_style_format(workbook.add_format(), &table.group_style);
+    // Header
+    for header in table.headers.iter() {
+        let format = match header.column {
+            Some(column) => &head_row_formats[column],
+            None => &group_format,
+        };
+        let ((first_y, first_x), (last_y, last_x)) = (header.first, header.last);
+        if header.first == header.last {
+            s.write_string(first_y, first_x, header.text, Some(format))?;
+            debug!(
+                "(header)write_string -> y: {:?}, x: {:?}, text: {:?}",
+                first_y, first_x, header.text
+            );
+        } else {
+            s.merge_range(first_y, first_x, last_y, last_x, header.text, Some(format))?;
+            debug!("(header)merge_range -> start_y: {:?}, start_x: {:?}, end_y: {:?}, end_x: {:?}, text: {:?}", first_y, first_x, last_y, last_x, header.text);
+        }
+    }
+    // Body
+    for cell in table.cells.iter() {
+        let (y, x) = cell.pos;
+        let column = cell.column;
+        let cell_format = match cell.kind {
+            CellKind::Body => &data_row_formats[column],
+            CellKind::Highlight => &highlight_formats[column],
+            CellKind::Value(idx) => &value_formats[column][idx],
+        };
+        // the highlight is applied on the format, so the fonts keep the body style
+        let cell_style = match cell.kind {
+            CellKind::Value(idx) => &table.value_styles[column][idx],
+            _ => &column_styles[column].body,
+        };
+        match cell.spans {
+            // a rich string needs two fragments at least
+            Some(spans) if spans.len() > 1 => {
+                // fragments don't inherit the font of the cell
+                let formats: Vec<Format<'_>> = spans
+                    .iter()
+                    .map(|span| {
+                        add_font_format(
+                            add_style_font(workbook.add_format(), cell_style),
+                            span.style(),
+                        )
+                    })
+                    .collect();
+                let fragments: Vec<(&str, Option<&Format<'_>>)> = spans
+                    .iter()
+                    .zip(formats.iter())
+                    .map(|(span, format)| (span.text(), Some(format)))
+                    .collect();
+                s.write_rich_string(y, x, &fragments, Some(cell_format))?;
+            }
+            Some(spans) => {
+                let mut format = add_style_format(workbook.add_format(), cell_style);
+                if cell.kind == CellKind::Highlight {
+                    format = add_highlight_format(format);
+                }
+                let format = add_font_format(format, spans[0].style());
+                s.write_string(y, x, cell.text, Some(&format))?;
+            }
+            None => {
+                s.write_string(y, x, cell.text, Some(cell_format))?;
+            }
+        }
+        debug!(
+            "(content)write_string -> y: {:?}, x: {:?}, text: {:?}",
+            y, x, cell.text
+        );
+    }
+    let b = table.block;
+    if table.autofilter {
+        // the header row right above the body has the filter buttons
+        s.autofilter(
+            table.header_y,
+            table.x,
+            table.last_row,
+            table.x + b.columns.len() as u16 - 1,
+        )?;
+    }
+    for (x_offset, column) in b.columns.iter().enumerate() {
+        if let (true, Some(values)) = (column.dropdown, &column.enum_values) {
+            let mut validation = DataValidation::new(
+                DataValidationType::List,
+                DataValidationCriteria::Between,
+                DataValidationErrorType::Stop,
+            );
+            validation.value_list = Some(values.clone());
+            let x = table.x + x_offset as u16;
+            s.data_validation_range(table.body_start_y, x, table.last_row, x, &validation)?;
+        }
+    }
+    Ok(())
+}
+
+/// Renders the cover sheet listing the statistics of every sheet with the links to them.
+fn export_cover(
+    data: &Data,
+    workbook: &Workbook,
+    cover: &rule::Cover,
+    styles: &Styles,
+) -> Result<()> {
+    let summaries = summarize(data.sheets(), data.rule());
+    if summaries.iter().any(|summary| summary.name == cover.name) {
+        return Err(anyhow::anyhow!(
+            "the cover sheet '{}' conflicts with the sheet of the same name",
+            cover.name
+        ));
+    }
+    let general = &data.rule().general;
+    let mut s = workbook.add_worksheet(Some(&cover.name))?;
+    if let Some(copyright) = &general.copyright {
+        s.set_footer(&format!("&C{}", copyright.replace("&", "&&")))?;
+    }
+    let title_format = add_style_format(workbook.add_format(), &styles.title);
+    let header_format = add_style_format(workbook.add_format(), &styles.header);
+    let body_format = add_style_format(workbook.add_format(), &styles.body);
+    let link_format = add_style_format(workbook.add_format(), &styles.body)
+        .set_font_color(FormatColor::Blue)
+        .set_underline(FormatUnderline::Single);
+    s.write_string(
+        0,
+        0,
+        general.title.as_deref().unwrap_or(&cover.name),
+        Some(&title_format),
+    )?;
+    // the date of the export is used if the rule has no date
+    let date = general
+        .date
+        .clone()
+        .unwrap_or_else(|| chrono::Local::now().format("%Y-%m-%d").to_string());
+    let mut y = 2;
+    for (label, value) in [
+        ("Version", general.version.as_deref()),
+        ("Date", Some(&date)),
+    ]
+    .iter()
+    {
+        if let Some(value) = value {
+            s.write_string(y, 0, label, Some(&header_format))?;
+            s.write_string(y, 1, value, Some(&body_format))?;
+            y += 1;
+        }
+    }
+    // Header
+    y += 1;
+    let keys = count_keys(&summaries);
+    let mut headers = vec![
+        String::from("Sheet"),
+        String::from("Blocks"),
+        String::from("Rows"),
+    ];
+    if let Some(count_by) = &cover.count_by {
+        headers.extend(keys.iter().map(|key| format!("{}: {}", count_by, key)));
+    }
+    let mut column_widths: Vec<f64> = headers
+        .iter()
+        .map(|header| estimate_column_width(header))
+        .collect();
+    for (x, header) in headers.iter().enumerate() {
+        s.write_string(y, x as u16, header, Some(&header_format))?;
+    }
+    // Body
+    for summary in summaries.iter() {
+        y += 1;
+        // quotes in the sheet name are escaped by doubling them
+        let link = format!("internal:'{}'!A1", summary.name.replace("'", "''"));
+        s.write_url(y, 0, &link, Some(&link_format))?;
+        s.write_string(y, 0, &summary.name, Some(&link_format))?;
+        let blocks = summary.blocks.join("\n");
+        s.write_string(y, 1, &blocks, Some(&body_format))?;
+        s.write_number(y, 2, summary.rows as f64, Some(&body_format))?;
+        if cover.count_by.is_some() {
+            for (offset, key) in keys.iter().enumerate() {
+                let count = summary.counts.get(*key).copied().unwrap_or(0);
+                s.write_number(y, 3 + offset as u16, count as f64, Some(&body_format))?;
+            }
+        }
+        for (x, text) in [&summary.name, &blocks].iter().enumerate() {
+            column_widths[x] = column_widths[x].max(estimate_column_width(text));
+        }
+    }
+    for (x, width) in column_widths.iter().enumerate() {
+        s.set_column(x as u16, x as u16, *width, None)?;
+    }
+    Ok(())
+}
+
+/// Applies the style defined in the rule to the format.
+fn add_style_format<'a>(format: Format<'a>, style: &CellStyle) -> Format<'a> {
+    let mut format = add_style_font(format, style);
+    if let Some(background) = style.background {
+        format = format.set_bg_color(FormatColor::Custom(background));
+    }
+    if let Some(border) = style.border {
+        format = format.set_border(match border {
+            style::Border::None => FormatBorder::None,
+            style::Border::Thin => FormatBorder::Thin,
+            style::Border::Medium => FormatBorder::Medium,
+            style::Border::Thick => FormatBorder::Thick,
+            style::Border::Dashed => FormatBorder::Dashed,
+            style::Border::Dotted => FormatBorder::Dotted,
+            style::Border::Double => FormatBorder::Double,
+            style::Border::Hair => FormatBorder::Hair,
+        });
+    }
+    if let Some(border_color) = style.border_color {
+        format = format.set_border_color(FormatColor::Custom(border_color));
+    }
+    if let Some(align) = style.align {
+        format = format.set_align(match align {
+            style::Align::Left => FormatAlignment::Left,
+            style::Align::Center => FormatAlignment::Center,
+            style::Align::Right => FormatAlignment::Right,
+            style::Align::Justify => FormatAlignment::Justify,
+            style::Align::CenterAcross => FormatAlignment::CenterAcross,
+        });
+    }
+    if let Some(valign) = style.valign {
+        format = format.set_align(match valign {
+            style::VerticalAlign::Top => FormatAlignment::VerticalTop,
+            style::VerticalAlign::Center => FormatAlignment::VerticalCenter,
+            style::VerticalAlign::Bottom => FormatAlignment::VerticalBottom,
+        });
+    }
+    if style.wrap == Some(true) {
+        format = format.set_text_wrap();
+    }
+    format
+}
+
+/// Applies the font of the style defined in the rule to the format.
+fn add_style_font<'a>(mut format: Format<'a>, style: &CellStyle) -> Format<'a> {
+    if let Some(font) = &style.font {
+        format = format.set_font_name(font);
+    }
+    if let Some(size) = style.size {
+        format = format.set_font_size(size);
+    }
+    if style.bold == Some(true) {
+        format = format.set_bold();
+    }
+    if style.italic == Some(true) {
+        format = format.set_italic();
+    }
+    if let Some(color) = style.color {
+        format = format.set_font_color(FormatColor::Custom(color));
+    }
+    format
+}
+
+/// Fills the cell violating the column constraints in red.
+fn add_highlight_format(format: Format<'_>) -> Format<'_> {
+    format
+        .set_bg_color(FormatColor::Custom(0xFFC7CE))
+        .set_font_color(FormatColor::Custom(0x9C0006))
+}
+
+/// Applies the font matching the inline formatting to the format.
+fn add_font_format<'a>(mut format: Format<'a>, style: &Style) -> Format<'a> {
+    if style.bold {
+        format = format.set_bold();
+    }
+    if style.italic {
+        format = format.set_italic();
+    }
+    if style.strikethrough {
+        format = format.set_font_strikeout();
+    }
+    if style.code {
+        format = format.set_font_name("Consolas");
+    }
+    if style.link.is_some() {
+        format = format
+            .set_underline(FormatUnderline::Single)
+            .set_font_color(FormatColor::Blue);
+    }
+    format
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use super::*;
+
+    use crate::rule::Rule;
+
+    fn get_default_rule() -> Rule {
+        Rule::marshal(&read_to_string("test_case/rule/default_rule.yml").unwrap()).unwrap()
+    }
+
+    #[cfg(feature = "excel")]
+    #[test]
+    fn test_warnings() {
+        let mut rule =
+            Rule::marshal(&read_to_string("test_case/rule/named_block.yml").unwrap()).unwrap();
+        rule.doc.blocks[1].autofilter = true;
+        // only one block has the autofilter in each sheet
+        let data = Data::marshal(
+            "# S\n* a\n---\n## C\n# T\n--- Test Cases\n## D",
+            rule.clone(),
+        )
+        .unwrap();
+        assert!(ExcelExporter.warnings(&data).unwrap().is_empty());
+        rule.doc.blocks[0].autofilter = true;
+        let data = Data::marshal("# S\n* a\n---\n## C\n# T\n* b", rule).unwrap();
+        assert_eq!(
+            vec![String::from("autofilter of block 'Test Cases' in sheet 'S' is ignored, since excel allows only one autofilter in a sheet and block 'Preconditions' has it.")],
+            ExcelExporter.warnings(&data).unwrap()
+        );
+    }
+
+    #[cfg(feature = "excel")]
+    #[test]
+    fn test_export_excel_with_inline_format() {
+        let data = Data::marshal(
+            &read_to_string("test_case/input/inline_format.md").unwrap(),
+            get_default_rule(),
+        )
+        .unwrap();
+        let file_name = "unit_test_inline_format";
+        assert!(ExcelExporter.export(&data, file_name).is_ok());
+        std::fs::remove_file(format!("{}.xlsx", file_name)).unwrap();
+    }
+
+    #[cfg(feature = "excel")]
+    #[test]
+    fn test_export_excel_with_highlight() {
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/constraint.yml").unwrap()).unwrap();
+        let data = Data::marshal(
+            &read_to_string("test_case/input/constraint.md").unwrap(),
+            rule,
+        )
+        .unwrap();
+        let file_name = "unit_test_highlight";
+        assert!(ExcelExporter.export(&data, file_name).is_ok());
+        std::fs::remove_file(format!("{}.xlsx", file_name)).unwrap();
+    }
+
+    #[cfg(feature = "excel")]
+    #[test]
+    fn test_export_excel_with_style() {
+        let rule = Rule::marshal(&read_to_string("test_case/rule/style.yml").unwrap()).unwrap();
+        let data = Data::marshal("# Sheet\n* Description 1\n* **Description 2**", rule).unwrap();
+        let file_name = "unit_test_style";
+        assert!(ExcelExporter.export(&data, file_name).is_ok());
+        std::fs::remove_file(format!("{}.xlsx", file_name)).unwrap();
+    }
+
+    #[cfg(feature = "excel")]
+    #[test]
+    fn test_export_excel_with_layout() {
+        let rule = Rule::marshal(&read_to_string("test_case/rule/layout.yml").unwrap()).unwrap();
+        let data = Data::marshal(
+            &read_to_string("test_case/input/multi_block_multi_row.md").unwrap(),
+            rule,
+        )
+        .unwrap();
+        let file_name = "unit_test_layout";
+        assert!(ExcelExporter.export(&data, file_name).is_ok());
+        std::fs::remove_file(format!("{}.xlsx", file_name)).unwrap();
+    }
+
+    #[cfg(feature = "excel")]
+    #[test]
+    fn test_export_excel_with_filter() {
+        let rule = Rule::marshal(&read_to_string("test_case/rule/filter.yml").unwrap()).unwrap();
+        let data =
+            Data::marshal(&read_to_string("test_case/input/filter.md").unwrap(), rule).unwrap();
+        let file_name = "unit_test_filter";
+        assert!(ExcelExporter.export(&data, file_name).is_ok());
+        std::fs::remove_file(format!("{}.xlsx", file_name)).unwrap();
+    }
+
+    #[cfg(feature = "excel")]
+    #[test]
+    fn test_export_excel_with_cover() {
+        let rule = Rule::marshal(&read_to_string("test_case/rule/cover.yml").unwrap()).unwrap();
+        let data = Data::marshal(
+            &read_to_string("test_case/input/cover.md").unwrap(),
+            rule.clone(),
+        )
+        .unwrap();
+        let file_name = "unit_test_cover";
+        assert!(ExcelExporter.export(&data, file_name).is_ok());
+        std::fs::remove_file(format!("{}.xlsx", file_name)).unwrap();
+        // the cover sheet must not have the same name as the other sheets
+        let data = Data::marshal("# Summary\n## Login\nTester: Alice", rule).unwrap();
+        assert!(ExcelExporter
+            .export(&data, "unit_test_cover_conflict")
+            .is_err());
+        let _ = std::fs::remove_file("unit_test_cover_conflict.xlsx");
+    }
+
+    #[cfg(feature = "excel")]
+    #[test]
+    fn test_export_excel() {
+        let rule = get_default_rule();
+        let data = Data::marshal(
+            &read_to_string("test_case/input/single_block_multi_row.md").unwrap(),
+            rule,
+        )
+        .unwrap();
+        let file_name = "unit_test";
+        assert!(ExcelExporter.export(&data, "unit_test").is_ok());
+        std::fs::remove_file(format!("{}.xlsx", file_name)).unwrap();
+    }
+
+    #[cfg(feature = "excel")]
+    #[test]
+    fn test_export_excel_with_copyright() {
+        let rule = Rule::marshal(&read_to_string("test_case/rule/general.yml").unwrap()).unwrap();
+        let data =
+            Data::marshal(&read_to_string("test_case/input/general.md").unwrap(), rule).unwrap();
+        let file_name = "unit_test_copyright";
+        assert!(ExcelExporter.export(&data, file_name).is_ok());
+        std::fs::remove_file(format!("{}.xlsx", file_name)).unwrap();
+    }
+}
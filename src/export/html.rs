@@ -0,0 +1,309 @@
+#[cfg(not(test))]
+use log::info;
+
+#[cfg(test)]
+use std::println as info;
+
+use std::{fs, io::Write};
+
+use anyhow::Result;
+
+use crate::{
+    constant::HTML_STYLE,
+    constraint::Constraints,
+    data::{Block, Data, Row},
+    export::Exporter,
+    rule, summary,
+    utils::{escape_html, is_safe_link, CellRange},
+};
+
+/// `.html` to browse the document in a browser.
+pub struct HtmlExporter;
+
+impl Exporter for HtmlExporter {
+    fn name(&self) -> &str {
+        "html"
+    }
+
+    fn extension(&self) -> &str {
+        "html"
+    }
+
+    /// Exports the whole document as `<file_name>.html`.
+    /// Each sheet is a collapsible section and each block is a table.
+    fn export(&self, data: &Data, file_name: &str) -> Result<()> {
+        info!("exporting html file ({}.html)...", file_name);
+        fs::write(format!("{}.html", file_name), to_html(data)?)?;
+        info!("OK");
+        Ok(())
+    }
+
+    fn write(&self, data: &Data, writer: &mut dyn Write) -> Result<()> {
+        writer.write_all(to_html(data)?.as_bytes())?;
+        Ok(())
+    }
+}
+
+fn to_html(data: &Data) -> Result<String> {
+    let general = &data.rule().general;
+    let title = general
+        .title
+        .as_deref()
+        .or_else(|| data.sheets().first().and_then(|sheet| sheet.name()))
+        .unwrap_or_default();
+    let mut html = vec![
+        String::from("<!DOCTYPE html>"),
+        String::from("<html>"),
+        String::from("<head>"),
+        String::from("<meta charset=\"utf-8\">"),
+        format!("<title>{}</title>", escape_html(title)),
+        format!("<style>\n{}\n</style>", HTML_STYLE),
+        String::from("</head>"),
+        String::from("<body>"),
+    ];
+    if let Some(title) = &general.title {
+        html.push(format!("<h1>{}</h1>", escape_html(title)));
+    }
+    let subtitle: Vec<String> = [("Version", &general.version), ("Date", &general.date)]
+        .iter()
+        .filter_map(|(label, value)| {
+            value
+                .as_ref()
+                .map(|value| format!("{}: {}", label, escape_html(value)))
+        })
+        .collect();
+    if !subtitle.is_empty() {
+        html.push(format!("<p>{}</p>", subtitle.join(" / ")));
+    }
+    // links to the sheets
+    let sheet_names: Vec<String> = data
+        .sheets()
+        .iter()
+        .enumerate()
+        .map(|(idx, sheet)| summary::sheet_name(sheet, idx))
+        .collect();
+    html.push(String::from("<nav>"));
+    for (idx, name) in sheet_names.iter().enumerate() {
+        html.push(format!(
+            "<a href=\"#sheet-{}\">{}</a>",
+            idx + 1,
+            escape_html(name)
+        ));
+    }
+    html.push(String::from("</nav>"));
+    for (idx, (sheet, name)) in data.sheets().iter().zip(sheet_names.iter()).enumerate() {
+        html.push(format!("<details id=\"sheet-{}\" open>", idx + 1));
+        html.push(format!("<summary>{}</summary>", escape_html(name)));
+        for block in sheet.blocks().iter() {
+            if let Some(b) = data.rule().doc.blocks.get(block.rule_idx()) {
+                html.push(block_to_html(data, b, block)?);
+            }
+        }
+        html.push(String::from("</details>"));
+    }
+    if let Some(copyright) = &general.copyright {
+        html.push(format!("<footer>{}</footer>", escape_html(copyright)));
+    }
+    html.push(String::from("</body>"));
+    html.push(String::from("</html>"));
+    Ok(html.into_iter().map(|line| format!("{}\n", line)).collect())
+}
+
+/// Renders the block as a table.
+/// If the block has groups, the group titles are merged on the row above the column titles
+/// and the columns without group span both rows, in the same way as the excel output.
+fn block_to_html(data: &Data, rule_block: &rule::Block, block: &Block) -> Result<String> {
+    let mut lines = vec![String::from("<section>")];
+    lines.push(format!("<h2>{}</h2>", escape_html(block.title())));
+    lines.push(String::from("<table>"));
+    // Header
+    lines.push(String::from("<thead>"));
+    if rule_block.merge_info.is_empty() {
+        lines.push(format!(
+            "<tr>{}</tr>",
+            rule_block
+                .columns
+                .iter()
+                .map(|column| format!("<th>{}</th>", escape_html(&column.title)))
+                .collect::<String>()
+        ));
+    } else {
+        let (mut group_row, mut column_row) = (String::new(), String::new());
+        for (pos_x, column) in rule_block.columns.iter().enumerate() {
+            let pos_x = pos_x as u16;
+            match rule_block
+                .merge_info
+                .iter()
+                .find(|merge_info| CellRange::new(merge_info.from, merge_info.to).contain(pos_x))
+            {
+                Some(merge_info) => {
+                    if merge_info.from == pos_x {
+                        group_row.push_str(&format!(
+                            "<th colspan=\"{}\">{}</th>",
+                            merge_info.to - merge_info.from + 1,
+                            escape_html(&merge_info.title)
+                        ));
+                    }
+                    column_row.push_str(&format!("<th>{}</th>", escape_html(&column.title)));
+                }
+                None => group_row.push_str(&format!(
+                    "<th rowspan=\"2\">{}</th>",
+                    escape_html(&column.title)
+                )),
+            }
+        }
+        lines.push(format!("<tr>{}</tr>", group_row));
+        lines.push(format!("<tr>{}</tr>", column_row));
+    }
+    lines.push(String::from("</thead>"));
+    // Body
+    lines.push(String::from("<tbody>"));
+    let constraints = Constraints::new(&rule_block.columns)?;
+    for row in block.rows().iter() {
+        let violated: Vec<usize> = if data.rule().general.highlight {
+            constraints
+                .check(row.cells())
+                .into_iter()
+                .map(|(idx, _)| idx)
+                .collect()
+        } else {
+            vec![]
+        };
+        let cells: String = (0..row.cells().len())
+            .map(|idx| {
+                let class = if violated.contains(&idx) {
+                    " class=\"violation\""
+                } else {
+                    ""
+                };
+                format!("<td{}>{}</td>", class, cell_to_html(row, idx))
+            })
+            .collect();
+        lines.push(format!("<tr>{}</tr>", cells));
+    }
+    lines.push(String::from("</tbody>"));
+    lines.push(String::from("</table>"));
+    lines.push(String::from("</section>"));
+    Ok(lines.join("\n"))
+}
+
+/// Renders the cell with its inline formatting.
+/// Line breaks are kept by `white-space: pre-wrap` of the cell.
+fn cell_to_html(row: &Row, idx: usize) -> String {
+    match row.spans(idx) {
+        Some(spans) => spans
+            .iter()
+            .map(|span| {
+                let style = span.style();
+                let mut text = escape_html(span.text());
+                if style.code {
+                    text = format!("<code>{}</code>", text);
+                }
+                if style.strikethrough {
+                    text = format!("<del>{}</del>", text);
+                }
+                if style.italic {
+                    text = format!("<em>{}</em>", text);
+                }
+                if style.bold {
+                    text = format!("<strong>{}</strong>", text);
+                }
+                // the link with other schemes like `javascript:` is rendered as the text only
+                if let Some(link) = style.link.as_ref().filter(|link| is_safe_link(link)) {
+                    text = format!("<a href=\"{}\">{}</a>", escape_html(link), text);
+                }
+                text
+            })
+            .collect(),
+        None => escape_html(&row.cells()[idx]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use super::*;
+
+    use crate::rule::Rule;
+
+    fn get_default_rule() -> Rule {
+        Rule::marshal(&read_to_string("test_case/rule/default_rule.yml").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_to_html() {
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/multi_block.yml").unwrap()).unwrap();
+        let data = Data::marshal(
+            &read_to_string("test_case/input/multi_block_multi_row.md").unwrap(),
+            rule,
+        )
+        .unwrap();
+        let html = to_html(&data).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>\n"));
+        assert!(html.contains("<a href=\"#sheet-1\">Sheet Name</a>"));
+        assert!(html.contains("<details id=\"sheet-1\" open>\n<summary>Sheet Name</summary>"));
+        assert!(html.contains("<h2>Block Title 1</h2>"));
+        // the group is merged and the other columns span the two header rows
+        assert!(html.contains(
+            "<tr><th rowspan=\"2\">No</th><th colspan=\"7\">Variation</th><th rowspan=\"2\">Description</th></tr>\n<tr><th>Variation 1</th>"
+        ));
+        assert!(html.contains("<td>Test Description\nmore lines...</td>"));
+    }
+
+    #[test]
+    fn test_to_html_inline_format() {
+        let data = Data::marshal(
+            &read_to_string("test_case/input/inline_format.md").unwrap(),
+            get_default_rule(),
+        )
+        .unwrap();
+        let html = to_html(&data).unwrap();
+        assert!(html.contains("<td>Test <code>Variation</code> 1</td>"));
+        assert!(html.contains(
+            "<td>Open <code>config.yml</code> and set <strong>bold</strong> <em>italic</em> <del>strike</del>\nsee <a href=\"https://example.com\">the docs</a><code>\ncode only line</code></td>"
+        ));
+    }
+
+    #[test]
+    fn test_to_html_unsafe_link() {
+        let data = Data::marshal(
+            "# Sheet Name\n## [click](javascript:alert(1)) [mail](mailto:a@example.com) [doc](docs/a.html)",
+            get_default_rule(),
+        )
+        .unwrap();
+        let html = to_html(&data).unwrap();
+        assert!(html.contains(
+            "<td>click <a href=\"mailto:a@example.com\">mail</a> <a href=\"docs/a.html\">doc</a></td>"
+        ));
+        assert!(!html.contains("javascript"));
+    }
+
+    #[test]
+    fn test_to_html_highlight() {
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/constraint.yml").unwrap()).unwrap();
+        let data = Data::marshal(
+            &read_to_string("test_case/input/constraint.md").unwrap(),
+            rule,
+        )
+        .unwrap();
+        assert!(to_html(&data).unwrap().contains("<td class=\"violation\">"));
+    }
+
+    #[test]
+    fn test_export_html() {
+        let rule = Rule::marshal(&read_to_string("test_case/rule/cover.yml").unwrap()).unwrap();
+        let data =
+            Data::marshal(&read_to_string("test_case/input/cover.md").unwrap(), rule).unwrap();
+        let file_name = "unit_test_html";
+        assert!(HtmlExporter.export(&data, file_name).is_ok());
+        let path = format!("{}.html", file_name);
+        let html = read_to_string(&path).unwrap();
+        assert!(html.contains("<title>Login Test Report</title>"));
+        assert!(html.contains("<p>Version: 1.2 / Date: 2021-04-01</p>"));
+        assert!(html.contains("<a href=\"#sheet-2\">Logout</a>"));
+        std::fs::remove_file(path).unwrap();
+    }
+}
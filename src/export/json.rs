@@ -0,0 +1,151 @@
+#[cfg(not(test))]
+use log::info;
+
+#[cfg(test)]
+use std::println as info;
+
+use std::{fs, io::Write};
+
+use anyhow::Result;
+use serde_json::{json, Map, Value};
+
+use crate::{data::Data, export::Exporter};
+
+/// `.json` of the whole document.
+pub struct JsonExporter;
+
+impl Exporter for JsonExporter {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn extension(&self) -> &str {
+        "json"
+    }
+
+    /// Exports the whole document as `<file_name>.json`.
+    /// Each row is a map keyed by the column titles in the rule.
+    fn export(&self, data: &Data, file_name: &str) -> Result<()> {
+        info!("exporting json file ({}.json)...", file_name);
+        fs::write(
+            format!("{}.json", file_name),
+            serde_json::to_string_pretty(&to_json(data))?,
+        )?;
+        info!("OK");
+        Ok(())
+    }
+
+    fn write(&self, data: &Data, writer: &mut dyn Write) -> Result<()> {
+        writer.write_all(serde_json::to_string_pretty(&to_json(data))?.as_bytes())?;
+        Ok(())
+    }
+}
+
+pub(crate) fn to_json(data: &Data) -> Value {
+    let sheets: Vec<Value> = data
+        .sheets()
+        .iter()
+        .map(|sheet| {
+            let blocks: Vec<Value> = sheet
+                .blocks()
+                .iter()
+                .map(|block| {
+                    let columns = data.columns(block.rule_idx());
+                    let rows: Vec<Value> = block
+                        .rows()
+                        .iter()
+                        .map(|row| {
+                            let mut cells = Map::new();
+                            for (column, cell) in columns.iter().zip(row.cells().iter()) {
+                                cells.insert(column.title.clone(), Value::from(cell.as_str()));
+                            }
+                            Value::Object(cells)
+                        })
+                        .collect();
+                    json!({
+                        "title": block.title(),
+                        "columns": columns
+                            .iter()
+                            .map(|column| json!({
+                                "title": column.title,
+                                "group": column.group.as_ref().map(|g| g.title.clone()),
+                            }))
+                            .collect::<Vec<Value>>(),
+                        "rows": rows,
+                    })
+                })
+                .collect();
+            json!({
+                "name": sheet.name(),
+                "blocks": blocks,
+            })
+        })
+        .collect();
+    json!({ "sheets": sheets })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use super::*;
+
+    use crate::rule::Rule;
+
+    fn get_default_rule() -> Rule {
+        Rule::marshal(&read_to_string("test_case/rule/default_rule.yml").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_to_json() {
+        let rule = Rule::marshal(&read_to_string("test_case/rule/general.yml").unwrap()).unwrap();
+        let data =
+            Data::marshal(&read_to_string("test_case/input/general.md").unwrap(), rule).unwrap();
+        let expected = json!({
+            "sheets": [{
+                "name": "Sheet Name",
+                "blocks": [{
+                    "title": "Block Title",
+                    "columns": [
+                        { "title": "No", "group": null },
+                        { "title": "Variation 1", "group": null },
+                        { "title": "Description", "group": null },
+                    ],
+                    "rows": [
+                        {
+                            "No": "IT-001",
+                            "Variation 1": "Test Variation 1",
+                            "Description": "Test Description 1",
+                        },
+                        {
+                            "No": "IT-002",
+                            "Variation 1": "Test Variation 2",
+                            "Description": "Test Description 2",
+                        },
+                    ],
+                }],
+            }],
+        });
+        assert_eq!(expected, to_json(&data));
+    }
+
+    #[test]
+    fn test_export_json() {
+        let rule = get_default_rule();
+        let data = Data::marshal(
+            &read_to_string("test_case/input/single_block_multi_row.md").unwrap(),
+            rule,
+        )
+        .unwrap();
+        let file_name = "unit_test_json";
+        assert!(JsonExporter.export(&data, file_name).is_ok());
+        let path = format!("{}.json", file_name);
+        let exported: Value = serde_json::from_str(&read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(to_json(&data), exported);
+        assert_eq!(
+            json!({ "title": "Variation 1", "group": "Variation" }),
+            exported["sheets"][0]["blocks"][0]["columns"][1]
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
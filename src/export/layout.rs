@@ -0,0 +1,420 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+
+use crate::{
+    constraint::Constraints,
+    data::{Block, Sheet, Span},
+    rule::{self, Direction, Rule},
+    style::{CellStyle, Styles},
+    utils::{estimate_column_width, estimate_row_height, CellRange},
+};
+
+/// The placement of the blocks in a sheet of the spreadsheet outputs (excel and ods).
+/// The writers only render it, so both outputs have the same layout.
+#[derive(Debug)]
+pub struct SheetLayout<'a> {
+    pub blocks: Vec<BlockLayout<'a>>,
+    /// widths of the columns set by the rule or estimated with `autoFit`
+    pub column_widths: BTreeMap<u16, f64>,
+    /// heights of the rows estimated with `autoFit`
+    pub row_heights: BTreeMap<u32, f64>,
+    /// the rows above it are frozen with `freeze`
+    pub freeze_row: Option<u32>,
+    /// the titles of the blocks whose `autofilter` is ignored, since excel allows only one autofilter in a sheet
+    pub ignored_autofilters: Vec<&'a str>,
+}
+
+/// A block placed in the sheet.
+#[derive(Debug)]
+pub struct BlockLayout<'a> {
+    /// 0-based row and column of the block title
+    pub title_pos: (u32, u16),
+    pub title: &'a str,
+    pub title_style: CellStyle,
+    /// the header and the body, which the block not defined in the rule doesn't have
+    pub table: Option<TableLayout<'a>>,
+}
+
+/// The header and the body of a block.
+#[derive(Debug)]
+pub struct TableLayout<'a> {
+    /// the definition of the block in the rule
+    pub block: &'a rule::Block,
+    /// the styles of each column merged with the styles of the document and the block
+    pub column_styles: Vec<Styles>,
+    /// the header style of the groups
+    pub group_style: CellStyle,
+    /// the body style merged with each value style of each column
+    pub value_styles: Vec<Vec<CellStyle>>,
+    pub headers: Vec<HeaderCell<'a>>,
+    pub cells: Vec<BodyCell<'a>>,
+    /// the header row right above the body
+    pub header_y: u32,
+    pub body_start_y: u32,
+    pub last_row: u32,
+    /// the first column of the block
+    pub x: u16,
+    /// the first block with `autofilter` in the sheet, since excel allows only one autofilter
+    pub autofilter: bool,
+}
+
+/// A header cell, which is merged from `first` to `last` if they're different.
+#[derive(Debug, PartialEq)]
+pub struct HeaderCell<'a> {
+    pub first: (u32, u16),
+    pub last: (u32, u16),
+    pub text: &'a str,
+    /// the index of the column, or none for the groups
+    pub column: Option<usize>,
+}
+
+/// A body cell.
+#[derive(Debug, PartialEq)]
+pub struct BodyCell<'a> {
+    pub pos: (u32, u16),
+    /// the index of the column
+    pub column: usize,
+    pub text: &'a str,
+    pub spans: Option<&'a [Span]>,
+    pub kind: CellKind,
+}
+
+/// Which style the body cell has.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CellKind {
+    Body,
+    /// violating the column constraints with `highlight`, which takes precedence over the value styles
+    Highlight,
+    /// matching the value style at the index
+    Value(usize),
+}
+
+impl<'a> SheetLayout<'a> {
+    /// Places the blocks of the sheet by the layout in the rule.
+    pub fn new(sheet: &'a Sheet, rule: &'a Rule) -> Result<Self> {
+        let layout = &rule.layout;
+        // the document level styles applied to every block
+        let doc_styles = Styles::builtin().merge(&rule.style);
+        let mut result = SheetLayout {
+            blocks: vec![],
+            column_widths: BTreeMap::new(),
+            row_heights: BTreeMap::new(),
+            freeze_row: None,
+            ignored_autofilters: vec![],
+        };
+        let mut autofilter_added = false;
+        let (mut block_start_y, mut block_start_x) = layout.origin;
+        for block in sheet.blocks().iter() {
+            let b = rule.doc.blocks.get(block.rule_idx());
+            let block_styles = match b {
+                Some(b) => doc_styles.merge(&b.style),
+                None => doc_styles.clone(),
+            };
+            let table = match b {
+                Some(b) => {
+                    let table = result.place_table(
+                        block,
+                        b,
+                        &block_styles,
+                        (block_start_y + 1, block_start_x),
+                        rule,
+                        !autofilter_added,
+                    )?;
+                    autofilter_added |= table.autofilter;
+                    if b.autofilter && !table.autofilter {
+                        result.ignored_autofilters.push(block.title());
+                    }
+                    // freezes the rows above the body of the first block
+                    if layout.freeze && result.freeze_row.is_none() {
+                        result.freeze_row = Some(table.body_start_y);
+                    }
+                    Some(table)
+                }
+                None => None,
+            };
+            // the last row and the number of columns of the block
+            let (last_row, width) = match &table {
+                Some(table) => (table.last_row, table.block.columns.len() as u16),
+                None => (block_start_y, 1),
+            };
+            result.blocks.push(BlockLayout {
+                title_pos: (block_start_y, block_start_x),
+                title: block.title(),
+                title_style: block_styles.title,
+                table,
+            });
+            // place the next block
+            match layout.direction {
+                Direction::Vertical => block_start_y = last_row + 1 + layout.gap,
+                Direction::Horizontal => block_start_x += width + layout.gap as u16,
+            }
+        }
+        Ok(result)
+    }
+
+    /// Places the header and the body of the block from `(y, x)`,
+    /// and updates the widths and heights of the sheet.
+    fn place_table(
+        &mut self,
+        block: &'a Block,
+        b: &'a rule::Block,
+        block_styles: &Styles,
+        (y, x): (u32, u16),
+        rule: &Rule,
+        can_add_autofilter: bool,
+    ) -> Result<TableLayout<'a>> {
+        let auto_fit = rule.layout.auto_fit;
+        let column_styles: Vec<Styles> = b
+            .columns
+            .iter()
+            .map(|column| block_styles.merge(&column.style))
+            .collect();
+        // Header
+        // the columns in the groups are on the second row, and the others span both rows
+        let header_merged = !b.merge_info.is_empty();
+        let mut headers: Vec<HeaderCell<'a>> = b
+            .merge_info
+            .iter()
+            .map(|merge_info| HeaderCell {
+                first: (y, x + merge_info.from),
+                last: (y, x + merge_info.to),
+                text: &merge_info.title,
+                column: None,
+            })
+            .collect();
+        for (pos_x, column) in b.columns.iter().enumerate() {
+            let in_merged_range = b
+                .merge_info
+                .iter()
+                .any(|m| CellRange::new(m.from, m.to).contain(pos_x as u16));
+            let column_x = x + pos_x as u16;
+            if let Some(width) = column
+                .width
+                .or_else(|| auto_fit.then(|| estimate_column_width(&column.title)))
+            {
+                self.fit_column(column_x, width);
+            }
+            let (first, last) = if in_merged_range {
+                ((y + 1, column_x), (y + 1, column_x))
+            } else if header_merged {
+                ((y, column_x), (y + 1, column_x))
+            } else {
+                ((y, column_x), (y, column_x))
+            };
+            headers.push(HeaderCell {
+                first,
+                last,
+                text: &column.title,
+                column: Some(pos_x),
+            });
+        }
+        let header_y = if header_merged { y + 1 } else { y };
+        // Body
+        let body_start_y = header_y + 1;
+        let constraints = Constraints::new(&b.columns)?;
+        // the compiled pattern of the value styles of each column
+        let patterns = b
+            .columns
+            .iter()
+            .map(|column| {
+                column
+                    .value_styles
+                    .iter()
+                    .map(|value_style| value_style.compile())
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let value_styles: Vec<Vec<CellStyle>> = b
+            .columns
+            .iter()
+            .zip(column_styles.iter())
+            .map(|(column, styles)| {
+                column
+                    .value_styles
+                    .iter()
+                    .map(|value_style| styles.body.merge(&value_style.style))
+                    .collect()
+            })
+            .collect();
+        let mut cells = vec![];
+        let mut last_y = 0;
+        for (y_offset, row) in block.rows().iter().enumerate() {
+            let violated: Vec<usize> = if rule.general.highlight {
+                constraints
+                    .check(row.cells())
+                    .into_iter()
+                    .map(|(idx, _)| idx)
+                    .collect()
+            } else {
+                vec![]
+            };
+            for (x_offset, text) in row.cells().iter().enumerate() {
+                let pos = (body_start_y + y_offset as u32, x + x_offset as u16);
+                let matched = b.columns[x_offset]
+                    .value_styles
+                    .iter()
+                    .zip(patterns[x_offset].iter())
+                    .position(|(value_style, regex)| value_style.is_match(text, regex.as_ref()));
+                let (kind, size) = match matched {
+                    _ if violated.contains(&x_offset) => {
+                        (CellKind::Highlight, column_styles[x_offset].body.size)
+                    }
+                    Some(idx) => (CellKind::Value(idx), value_styles[x_offset][idx].size),
+                    None => (CellKind::Body, column_styles[x_offset].body.size),
+                };
+                if auto_fit {
+                    if b.columns[x_offset].width.is_none() {
+                        self.fit_column(pos.1, estimate_column_width(text));
+                    }
+                    let height = estimate_row_height(text, size);
+                    let current = self.row_heights.entry(pos.0).or_insert(height);
+                    *current = current.max(height);
+                }
+                cells.push(BodyCell {
+                    pos,
+                    column: x_offset,
+                    text,
+                    spans: row.spans(x_offset),
+                    kind,
+                });
+            }
+            last_y = y_offset;
+        }
+        Ok(TableLayout {
+            block: b,
+            group_style: block_styles.header.clone(),
+            column_styles,
+            value_styles,
+            headers,
+            cells,
+            header_y,
+            body_start_y,
+            last_row: body_start_y + last_y as u32,
+            x,
+            autofilter: b.autofilter && can_add_autofilter,
+        })
+    }
+
+    /// Returns the title of the block with the autofilter.
+    pub fn autofilter_block(&self) -> Option<&'a str> {
+        self.blocks
+            .iter()
+            .find(|block| block.table.as_ref().is_some_and(|table| table.autofilter))
+            .map(|block| block.title)
+    }
+
+    /// Widens the column to `width` if it's narrower.
+    fn fit_column(&mut self, x: u16, width: f64) {
+        let current = self.column_widths.entry(x).or_insert(width);
+        *current = current.max(width);
+    }
+}
+
+impl TableLayout<'_> {
+    /// Returns the style of the header cell.
+    pub fn header_style(&self, header: &HeaderCell<'_>) -> &CellStyle {
+        match header.column {
+            Some(column) => &self.column_styles[column].header,
+            None => &self.group_style,
+        }
+    }
+
+    /// Returns the style of the body cell, in which the highlight is filled in red.
+    pub fn cell_style(&self, cell: &BodyCell<'_>) -> CellStyle {
+        let body_style = &self.column_styles[cell.column].body;
+        match cell.kind {
+            CellKind::Body => body_style.clone(),
+            CellKind::Highlight => body_style.merge(&highlight_style()),
+            CellKind::Value(idx) => self.value_styles[cell.column][idx].clone(),
+        }
+    }
+}
+
+/// The colors of the cell violating the column constraints.
+pub fn highlight_style() -> CellStyle {
+    CellStyle {
+        color: Some(0x9C0006),
+        background: Some(0xFFC7CE),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use super::*;
+
+    use crate::data::Data;
+
+    #[test]
+    fn test_sheet_layout() {
+        let rule = Rule::marshal(&read_to_string("test_case/rule/layout.yml").unwrap()).unwrap();
+        let data = Data::marshal(
+            &read_to_string("test_case/input/multi_block_multi_row.md").unwrap(),
+            rule,
+        )
+        .unwrap();
+        let sheet = SheetLayout::new(&data.sheets()[0], data.rule()).unwrap();
+        let block = &sheet.blocks[0];
+        let table = block.table.as_ref().unwrap();
+        // the group is merged above the columns, and the other columns span both rows
+        assert_eq!((4, 2), block.title_pos);
+        assert_eq!(
+            HeaderCell {
+                first: (5, 3),
+                last: (5, 9),
+                text: "Variation",
+                column: None,
+            },
+            table.headers[0]
+        );
+        assert_eq!(
+            HeaderCell {
+                first: (5, 2),
+                last: (6, 2),
+                text: "No",
+                column: Some(0),
+            },
+            table.headers[1]
+        );
+        assert_eq!((6, 7), (table.header_y, table.body_start_y));
+        assert_eq!((7, 2), table.cells[0].pos);
+        assert_eq!(Some(7), sheet.freeze_row);
+    }
+
+    #[test]
+    fn test_sheet_layout_undefined_block() {
+        let mut rule =
+            Rule::marshal(&read_to_string("test_case/rule/named_block.yml").unwrap()).unwrap();
+        rule.general.unmapped = rule::Unmapped::Warning;
+        let data = Data::marshal("# S\n* a\n---\n## C\n---\n* b", rule).unwrap();
+        let sheet = SheetLayout::new(&data.sheets()[0], data.rule()).unwrap();
+        // the block not defined in the rule has the title only, and the next block follows it
+        assert_eq!(3, sheet.blocks.len());
+        assert!(sheet.blocks[2].table.is_none());
+        assert_eq!(
+            sheet.blocks[1].table.as_ref().unwrap().last_row + 2,
+            sheet.blocks[2].title_pos.0
+        );
+    }
+
+    #[test]
+    fn test_sheet_layout_autofilter() {
+        let mut rule =
+            Rule::marshal(&read_to_string("test_case/rule/named_block.yml").unwrap()).unwrap();
+        rule.doc.blocks[0].autofilter = true;
+        rule.doc.blocks[1].autofilter = true;
+        let data = Data::marshal("# S\n* a\n---\n## C", rule).unwrap();
+        let sheet = SheetLayout::new(&data.sheets()[0], data.rule()).unwrap();
+        let autofilters: Vec<bool> = sheet
+            .blocks
+            .iter()
+            .map(|block| block.table.as_ref().unwrap().autofilter)
+            .collect();
+        assert_eq!(vec![true, false], autofilters);
+        assert_eq!(Some("Preconditions"), sheet.autofilter_block());
+        assert_eq!(vec!["Test Cases"], sheet.ignored_autofilters);
+    }
+}
@@ -0,0 +1,249 @@
+#[cfg(not(test))]
+use log::info;
+
+#[cfg(test)]
+use std::println as info;
+
+use std::{fs, io::Write};
+
+use anyhow::Result;
+
+use crate::{
+    data::{Block, Data, Row},
+    export::Exporter,
+    rule, summary,
+    utils::{escape_markdown, escape_table_cell, to_link_destination},
+};
+
+/// `.md` with a table for each block.
+pub struct MarkdownExporter;
+
+impl Exporter for MarkdownExporter {
+    fn name(&self) -> &str {
+        "md"
+    }
+
+    fn extension(&self) -> &str {
+        "md"
+    }
+
+    /// Exports the whole document as `<file_name>.md`, in which each block is a GitHub-flavored Markdown table.
+    fn export(&self, data: &Data, file_name: &str) -> Result<()> {
+        info!("exporting markdown file ({}.md)...", file_name);
+        fs::write(format!("{}.md", file_name), to_markdown_tables(data))?;
+        info!("OK");
+        Ok(())
+    }
+
+    fn write(&self, data: &Data, writer: &mut dyn Write) -> Result<()> {
+        writer.write_all(to_markdown_tables(data).as_bytes())?;
+        Ok(())
+    }
+}
+
+fn to_markdown_tables(data: &Data) -> String {
+    let mut sections = vec![];
+    for (idx, sheet) in data.sheets().iter().enumerate() {
+        sections.push(format!("# {}", summary::sheet_name(sheet, idx)));
+        for block in sheet.blocks().iter() {
+            if let Some(b) = data.rule().doc.blocks.get(block.rule_idx()) {
+                sections.push(format!("## {}", block.title()));
+                sections.push(block_to_markdown(b, block));
+            }
+        }
+    }
+    sections
+        .into_iter()
+        .map(|section| format!("{}\n", section))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Renders the block as a table.
+/// Markdown tables have only one header row, so the group titles are folded into the column titles.
+fn block_to_markdown(rule_block: &rule::Block, block: &Block) -> String {
+    let headers: Vec<String> = rule_block
+        .columns
+        .iter()
+        .map(|column| {
+            let title = match &column.group {
+                Some(group) => format!("{} / {}", group.title, column.title),
+                None => column.title.clone(),
+            };
+            escape_table_cell(&title)
+        })
+        .collect();
+    let mut lines = vec![
+        format!("| {} |", headers.join(" | ")),
+        format!("|{}", " --- |".repeat(headers.len())),
+    ];
+    for row in block.rows().iter() {
+        let cells: Vec<String> = (0..row.cells().len())
+            .map(|idx| cell_to_markdown(row, idx))
+            .collect();
+        lines.push(format!("| {} |", cells.join(" | ")));
+    }
+    lines.join("\n")
+}
+
+/// Renders the cell with its inline formatting.
+/// Table cells can not have line breaks, so they're converted into `<br>`.
+fn cell_to_markdown(row: &Row, idx: usize) -> String {
+    let spans = match row.spans(idx) {
+        Some(spans) => spans,
+        None => {
+            return row.cells()[idx]
+                .split('\n')
+                .map(|line| escape_table_cell(&escape_markdown(line)))
+                .collect::<Vec<String>>()
+                .join("<br>")
+        }
+    };
+    spans
+        .iter()
+        .map(|span| {
+            let style = span.style();
+            // the notations can not continue over the line breaks
+            span.text()
+                .split('\n')
+                .map(|line| {
+                    if line.is_empty() {
+                        return String::new();
+                    }
+                    let mut text = if style.code {
+                        // the code span is enclosed by more backticks than it has in a row
+                        let fence = "`"
+                            .repeat(line.split(|c| c != '`').map(str::len).max().unwrap_or(0) + 1);
+                        let padding = if line.starts_with('`') || line.ends_with('`') {
+                            " "
+                        } else {
+                            ""
+                        };
+                        format!(
+                            "{}{}{}{}{}",
+                            fence,
+                            padding,
+                            line.replace("|", "\\|"),
+                            padding,
+                            fence
+                        )
+                    } else {
+                        escape_table_cell(&escape_markdown(line))
+                    };
+                    if style.strikethrough {
+                        text = format!("~~{}~~", text);
+                    }
+                    if style.italic {
+                        text = format!("_{}_", text);
+                    }
+                    if style.bold {
+                        text = format!("__{}__", text);
+                    }
+                    if let Some(link) = &style.link {
+                        text = format!("[{}]({})", text, to_link_destination(link));
+                    }
+                    text
+                })
+                .collect::<Vec<String>>()
+                .join("<br>")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use super::*;
+
+    use crate::rule::Rule;
+
+    fn get_default_rule() -> Rule {
+        Rule::marshal(&read_to_string("test_case/rule/default_rule.yml").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_to_markdown_tables() {
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/multi_block.yml").unwrap()).unwrap();
+        let data = Data::marshal(
+            &read_to_string("test_case/input/multi_block_multi_row.md").unwrap(),
+            rule,
+        )
+        .unwrap();
+        let markdown = to_markdown_tables(&data);
+        let mut lines = markdown.lines();
+        assert_eq!(Some("# Sheet Name"), lines.next());
+        assert_eq!(Some(""), lines.next());
+        assert_eq!(Some("## Block Title 1"), lines.next());
+        assert_eq!(Some(""), lines.next());
+        assert_eq!(
+            Some("| No | Variation / Variation 1 | Variation / Variation 2 | Variation / Variation 3 | Variation / Variation 4 | Variation / Variation 5 | Variation / Variation 6 | Variation / Variation 7 | Description |"),
+            lines.next()
+        );
+        assert_eq!(
+            Some("| --- | --- | --- | --- | --- | --- | --- | --- | --- |"),
+            lines.next()
+        );
+        assert_eq!(
+            Some("| 1 | Test Variation A 1 | Test Variation A 1-1 | Test Variation A 1-1-1 | Test Variation A 1-1-1-1 | Test Variation A 1-1-1-1-1 | Test Variation A 1-1-1-1-1-1 | Test Variation A 1-1-1-1-1-1-1 | Test Description<br>more lines... |"),
+            lines.next()
+        );
+    }
+
+    #[test]
+    fn test_to_markdown_tables_inline_format() {
+        let data = Data::marshal(
+            &read_to_string("test_case/input/inline_format.md").unwrap(),
+            get_default_rule(),
+        )
+        .unwrap();
+        let markdown = to_markdown_tables(&data);
+        assert!(markdown.contains("| 1 | Test `Variation` 1 |"));
+        assert!(markdown.contains(
+            "| Open `config.yml` and set __bold__ _italic_ ~~strike~~<br>see [the docs](<https://example.com>)<br>`code only line` |"
+        ));
+        // pipes and tags in the text are escaped
+        let data = Data::marshal("# Sheet Name\n* a | b\n  &lt;c&gt;", get_default_rule()).unwrap();
+        let row = &data.sheets()[0].blocks()[0].rows()[0];
+        assert_eq!("a \\| b<br>&lt;c&gt;", cell_to_markdown(row, 8));
+    }
+
+    #[test]
+    fn test_to_markdown_tables_escape() {
+        let data = Data::marshal(
+            "# Sheet Name\n## 2 * 3 = snake_case [x] and `a``b` [doc](<docs/a b|c.md>)",
+            get_default_rule(),
+        )
+        .unwrap();
+        let markdown = to_markdown_tables(&data);
+        // the punctuation is not taken as the notations, and the link target is kept as is
+        assert!(
+            markdown.contains(
+                "| 1 | 2 \\* 3 = snake\\_case \\[x\\] and ```a``b``` [doc](<docs/a b\\|c.md>) |"
+            ),
+            "{}",
+            markdown
+        );
+        // the plain cell without the spans is also escaped
+        let data = Data::marshal("# Sheet Name\n## a_b * c", get_default_rule()).unwrap();
+        let row = &data.sheets()[0].blocks()[0].rows()[0];
+        assert!(row.spans(1).is_none());
+        assert_eq!("a\\_b \\* c", cell_to_markdown(row, 1));
+    }
+
+    #[test]
+    fn test_export_markdown() {
+        let rule = Rule::marshal(&read_to_string("test_case/rule/general.yml").unwrap()).unwrap();
+        let data =
+            Data::marshal(&read_to_string("test_case/input/general.md").unwrap(), rule).unwrap();
+        let file_name = "unit_test_markdown";
+        assert!(MarkdownExporter.export(&data, file_name).is_ok());
+        let path = format!("{}.md", file_name);
+        assert_eq!(
+            "# Sheet Name\n\n## Block Title\n\n| No | Variation 1 | Description |\n| --- | --- | --- |\n| IT-001 | Test Variation 1 | Test Description 1 |\n| IT-002 | Test Variation 2 | Test Description 2 |\n",
+            read_to_string(&path).unwrap()
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
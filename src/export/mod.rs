@@ -0,0 +1,265 @@
+use std::{fs, io::Write};
+
+use anyhow::{Context, Result};
+
+use crate::data::Data;
+
+pub mod csv;
+#[cfg(feature = "excel")]
+pub mod excel;
+pub mod html;
+pub mod json;
+pub mod layout;
+pub mod markdown;
+#[cfg(feature = "ods")]
+pub mod ods;
+
+pub use self::csv::CsvExporter;
+#[cfg(feature = "excel")]
+pub use self::excel::ExcelExporter;
+pub use self::html::HtmlExporter;
+pub use self::json::JsonExporter;
+pub use self::markdown::MarkdownExporter;
+#[cfg(feature = "ods")]
+pub use self::ods::OdsExporter;
+
+/// An output format of the document.
+/// Each built-in format is implemented in its own submodule.
+/// Implement this and register it to `Registry` to add a new format without changing `Data`.
+pub trait Exporter {
+    /// Returns the name to select this format with `--format`.
+    fn name(&self) -> &str;
+
+    /// Returns the extension of the output file without the leading dot.
+    fn extension(&self) -> &str;
+
+    /// Writes the parsed document into the output named `file_name` (without the extension).
+    /// The rule is available by `data.rule()`.
+    fn export(&self, data: &Data, file_name: &str) -> Result<()>;
+
+    /// Returns the settings in the rule which this format can't apply, to be reported as warnings.
+    fn warnings(&self, _data: &Data) -> Result<Vec<String>> {
+        Ok(vec![])
+    }
+
+    /// Writes the parsed document into `writer` instead of a file, e.g. to stream it to stdout.
+    /// By default, the output is exported into a private temporary directory and copied,
+    /// which works for the exporters writing a single `<file_name>.<extension>`.
+    fn write(&self, data: &Data, writer: &mut dyn Write) -> Result<()> {
+        // a private directory is created for every call,
+        // so the path can't be predicted or replaced by other users
+        let dir = tempfile::Builder::new().prefix("mddoc-").tempdir()?;
+        let file_name = dir.path().join("output");
+        let file_name = file_name
+            .to_str()
+            .with_context(|| "Temporary file path is malformed")?;
+        let path = format!("{}.{}", file_name, self.extension());
+        self.export(data, file_name)?;
+        writer.write_all(&fs::read(&path)?)?;
+        // the directory is removed on drop even if the export fails,
+        // but close it to report the failure of the removal
+        dir.close()?;
+        Ok(())
+    }
+}
+
+/// The exporters selectable by their names.
+pub struct Registry {
+    exporters: Vec<Box<dyn Exporter>>,
+}
+
+impl Registry {
+    /// Returns the registry without any exporter.
+    pub fn new() -> Self {
+        Registry { exporters: vec![] }
+    }
+
+    /// Returns the registry with the exporters enabled by the features.
+    /// The first one (`xlsx` if the excel feature is enabled, otherwise `csv`) is the default.
+    pub fn builtin() -> Self {
+        let mut registry = Registry::new();
+        #[cfg(feature = "excel")]
+        registry.register(Box::new(ExcelExporter));
+        registry.register(Box::new(CsvExporter));
+        registry.register(Box::new(JsonExporter));
+        registry.register(Box::new(HtmlExporter));
+        registry.register(Box::new(MarkdownExporter));
+        #[cfg(feature = "ods")]
+        registry.register(Box::new(OdsExporter));
+        registry
+    }
+
+    /// Adds the exporter.
+    /// The exporter replaces the registered one with the same name, keeping its position.
+    pub fn register(&mut self, exporter: Box<dyn Exporter>) {
+        match self
+            .exporters
+            .iter()
+            .position(|registered| registered.name() == exporter.name())
+        {
+            Some(idx) => self.exporters[idx] = exporter,
+            None => self.exporters.push(exporter),
+        }
+    }
+
+    /// Returns the exporter named `name`.
+    pub fn get(&self, name: &str) -> Option<&dyn Exporter> {
+        self.exporters
+            .iter()
+            .find(|exporter| exporter.name() == name)
+            .map(|exporter| exporter.as_ref())
+    }
+
+    /// Returns the names of the registered exporters in order.
+    pub fn names(&self) -> Vec<&str> {
+        self.exporters
+            .iter()
+            .map(|exporter| exporter.name())
+            .collect()
+    }
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Registry::builtin()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, fs::read_to_string, path::Path};
+
+    use super::*;
+
+    use crate::rule::Rule;
+
+    /// Writes the sheet names, as an exporter defined outside of this crate.
+    struct SheetListExporter;
+
+    impl Exporter for SheetListExporter {
+        fn name(&self) -> &str {
+            "sheets"
+        }
+
+        fn extension(&self) -> &str {
+            "txt"
+        }
+
+        fn export(&self, data: &Data, file_name: &str) -> Result<()> {
+            let names: Vec<&str> = data
+                .sheets()
+                .iter()
+                .filter_map(|sheet| sheet.name())
+                .collect();
+            std::fs::write(format!("{}.txt", file_name), names.join("\n"))?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_builtin() {
+        let registry = Registry::builtin();
+        let names = registry.names();
+        #[cfg(feature = "excel")]
+        assert_eq!(Some(&"xlsx"), names.first());
+        #[cfg(not(feature = "excel"))]
+        assert_eq!(Some(&"csv"), names.first());
+        for name in ["csv", "json", "html", "md"].iter() {
+            assert!(names.contains(name));
+        }
+        assert_eq!(Some("json"), registry.get("json").map(|e| e.extension()));
+        assert!(registry.get("unknown").is_none());
+    }
+
+    #[test]
+    fn test_write() {
+        let rule = Rule::marshal(&read_to_string("test_case/rule/general.yml").unwrap()).unwrap();
+        let data =
+            Data::marshal(&read_to_string("test_case/input/general.md").unwrap(), rule).unwrap();
+        let registry = Registry::builtin();
+        for name in registry.names() {
+            let exporter = registry.get(name).unwrap();
+            let mut written = vec![];
+            assert!(exporter.write(&data, &mut written).is_ok(), "{}", name);
+            // the same content as the exported file
+            if name != "csv" {
+                let file_name = format!("unit_test_write_{}", name);
+                exporter.export(&data, &file_name).unwrap();
+                let path = format!("{}.{}", file_name, exporter.extension());
+                let exported = std::fs::read(&path).unwrap();
+                std::fs::remove_file(path).unwrap();
+                // the excel and ods files have the timestamps, so only the size is compared
+                if name == "xlsx" || name == "ods" {
+                    assert_eq!(exported.len(), written.len(), "{}", name);
+                } else {
+                    assert_eq!(exported, written, "{}", name);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_write_by_default() {
+        let data = Data::marshal("# Sheet Name\n## Test", Rule::default()).unwrap();
+        let mut written = vec![];
+        assert!(SheetListExporter.write(&data, &mut written).is_ok());
+        assert_eq!(b"Sheet Name".to_vec(), written);
+    }
+
+    /// remembers the file name given to `export` and fails
+    struct FailingExporter(RefCell<String>);
+
+    impl Exporter for FailingExporter {
+        fn name(&self) -> &str {
+            "failing"
+        }
+
+        fn extension(&self) -> &str {
+            "txt"
+        }
+
+        fn export(&self, _data: &Data, file_name: &str) -> Result<()> {
+            *self.0.borrow_mut() = file_name.to_string();
+            std::fs::write(format!("{}.txt", file_name), "partial")?;
+            Err(anyhow::anyhow!("failed"))
+        }
+    }
+
+    #[test]
+    fn test_write_removes_temporary_directory() {
+        let data = Data::marshal("# Sheet Name\n## Test", Rule::default()).unwrap();
+        let exporter = FailingExporter(RefCell::new(String::new()));
+        assert!(exporter.write(&data, &mut vec![]).is_err());
+        let dir = Path::new(exporter.0.borrow().as_str())
+            .parent()
+            .unwrap()
+            .to_path_buf();
+        assert!(dir.starts_with(std::env::temp_dir()));
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn test_register() {
+        let mut registry = Registry::new();
+        assert!(registry.names().is_empty());
+        registry.register(Box::new(CsvExporter));
+        registry.register(Box::new(SheetListExporter));
+        assert_eq!(vec!["csv", "sheets"], registry.names());
+        // the exporter with the same name is replaced
+        registry.register(Box::new(CsvExporter));
+        assert_eq!(vec!["csv", "sheets"], registry.names());
+
+        let data = Data::marshal(
+            &read_to_string("test_case/input/various_list_multiple_sheet.md").unwrap(),
+            Rule::marshal(&read_to_string("test_case/rule/various_list.yml").unwrap()).unwrap(),
+        )
+        .unwrap();
+        let file_name = "unit_test_exporter";
+        let exporter = registry.get("sheets").unwrap();
+        assert!(exporter.export(&data, file_name).is_ok());
+        let path = format!("{}.{}", file_name, exporter.extension());
+        let names: Vec<&str> = data.sheets().iter().filter_map(|s| s.name()).collect();
+        assert_eq!(names.join("\n"), read_to_string(&path).unwrap());
+        std::fs::remove_file(path).unwrap();
+    }
+}
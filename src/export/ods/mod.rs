@@ -0,0 +1,130 @@
+#[cfg(not(test))]
+use log::info;
+
+#[cfg(test)]
+use std::println as info;
+
+use anyhow::Result;
+
+use crate::{
+    data::Data,
+    export::{layout::SheetLayout, Exporter},
+    summary,
+};
+
+use self::writer::Spreadsheet;
+
+mod writer;
+
+/// `.ods` for LibreOffice in the same layout as the excel output.
+pub struct OdsExporter;
+
+impl Exporter for OdsExporter {
+    fn name(&self) -> &str {
+        "ods"
+    }
+
+    fn extension(&self) -> &str {
+        "ods"
+    }
+
+    /// Exports the document as `<file_name>.ods`.
+    /// Inline formatting, drop-down lists, autofilters, frozen panes and the cover sheet
+    /// are only available in the excel output.
+    fn export(&self, data: &Data, file_name: &str) -> Result<()> {
+        info!("exporting ods file ({}.ods)...", file_name);
+        let mut spreadsheet = Spreadsheet::new(&format!("{}.ods", file_name));
+        for (idx, sheet) in data.sheets().iter().enumerate() {
+            let sheet_layout = SheetLayout::new(sheet, data.rule())?;
+            let table = spreadsheet.add_table(&summary::sheet_name(sheet, idx));
+            for block in sheet_layout.blocks.iter() {
+                let (title_y, title_x) = block.title_pos;
+                table.write_string(title_y, title_x, block.title, &block.title_style);
+                let block_table = match &block.table {
+                    Some(block_table) => block_table,
+                    None => continue,
+                };
+                // Header
+                for header in block_table.headers.iter() {
+                    let ((first_y, first_x), (last_y, last_x)) = (header.first, header.last);
+                    let style = block_table.header_style(header);
+                    if header.first == header.last {
+                        table.write_string(first_y, first_x, header.text, style);
+                    } else {
+                        table.merge_range(first_y, first_x, last_y, last_x, header.text, style);
+                    }
+                }
+                // Body
+                for cell in block_table.cells.iter() {
+                    let (y, x) = cell.pos;
+                    table.write_string(y, x, cell.text, &block_table.cell_style(cell));
+                }
+            }
+            for (x, width) in sheet_layout.column_widths.into_iter() {
+                table.set_column_width(x, width);
+            }
+            for (y, height) in sheet_layout.row_heights.into_iter() {
+                table.set_row_height(y, height);
+            }
+        }
+        spreadsheet.save()?;
+        info!("OK");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use super::*;
+
+    use crate::rule::Rule;
+
+    #[cfg(feature = "ods")]
+    #[test]
+    fn test_export_ods() {
+        use calamine::{open_workbook, Ods, Reader};
+
+        use crate::import::{to_markdown, Worksheet};
+
+        let rule = Rule::marshal(&read_to_string("test_case/rule/layout.yml").unwrap()).unwrap();
+        let data = Data::marshal(
+            &read_to_string("test_case/input/multi_block_multi_row.md").unwrap(),
+            rule.clone(),
+        )
+        .unwrap();
+        let file_name = "unit_test_ods";
+        assert!(OdsExporter.export(&data, file_name).is_ok());
+        let path = format!("{}.ods", file_name);
+        let mut workbook: Ods<_> = open_workbook(&path).unwrap();
+        let mut worksheets = vec![];
+        for name in workbook.sheet_names() {
+            let range = workbook.worksheet_range(&name).unwrap();
+            let (end_y, end_x) = range.end().unwrap();
+            let rows = (0..=end_y)
+                .map(|y| {
+                    (0..=end_x)
+                        .map(|x| {
+                            range
+                                .get_value((y, x))
+                                .map(|cell| cell.to_string())
+                                .unwrap_or_default()
+                        })
+                        .collect()
+                })
+                .collect();
+            worksheets.push(Worksheet { name, rows });
+        }
+        // the title is at the origin and the group header is merged above the columns
+        assert_eq!("Block Title 1", worksheets[0].rows[4][2]);
+        assert_eq!("Variation", worksheets[0].rows[5][3]);
+        assert_eq!("Variation 1", worksheets[0].rows[6][3]);
+        // the same layout as the excel output can be imported again
+        assert_eq!(
+            data,
+            Data::marshal(&to_markdown(&worksheets, &rule).unwrap(), rule).unwrap()
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,437 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs::File,
+    io::Write,
+};
+
+use anyhow::Result;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+use crate::{
+    style::{Align, Border, CellStyle, VerticalAlign},
+    utils::escape_html,
+};
+
+const MIMETYPE: &str = "application/vnd.oasis.opendocument.spreadsheet";
+
+const MANIFEST: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<manifest:manifest xmlns:manifest="urn:oasis:names:tc:opendocument:xmlns:manifest:1.0" manifest:version="1.2">
+<manifest:file-entry manifest:full-path="/" manifest:version="1.2" manifest:media-type="application/vnd.oasis.opendocument.spreadsheet"/>
+<manifest:file-entry manifest:full-path="content.xml" manifest:media-type="text/xml"/>
+</manifest:manifest>
+"#;
+
+const CONTENT_HEADER: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:style="urn:oasis:names:tc:opendocument:xmlns:style:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0" xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0" xmlns:fo="urn:oasis:names:tc:opendocument:xmlns:xsl-fo-compatible:1.0" office:version="1.2">
+"#;
+
+/// A minimal OpenDocument Spreadsheet writer, which supports the features used by `OdsExporter`.
+#[derive(Debug)]
+pub struct Spreadsheet {
+    path: String,
+    tables: Vec<Table>,
+}
+
+impl Spreadsheet {
+    pub fn new(path: &str) -> Self {
+        Spreadsheet {
+            path: path.to_string(),
+            tables: vec![],
+        }
+    }
+
+    /// Adds a sheet and returns it to write the cells.
+    pub fn add_table(&mut self, name: &str) -> &mut Table {
+        self.tables.push(Table::new(name));
+        self.tables.last_mut().unwrap()
+    }
+
+    /// Writes the spreadsheet into the file.
+    pub fn save(&self) -> Result<()> {
+        let mut zip = ZipWriter::new(File::create(&self.path)?);
+        // the mimetype must be the first entry and must not be compressed
+        zip.start_file(
+            "mimetype",
+            SimpleFileOptions::default().compression_method(CompressionMethod::Stored),
+        )?;
+        zip.write_all(MIMETYPE.as_bytes())?;
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        zip.start_file("META-INF/manifest.xml", options)?;
+        zip.write_all(MANIFEST.as_bytes())?;
+        zip.start_file("content.xml", options)?;
+        zip.write_all(self.content().as_bytes())?;
+        zip.finish()?;
+        Ok(())
+    }
+
+    fn content(&self) -> String {
+        // the cell styles are shared by all sheets
+        let mut styles: Vec<&CellStyle> = vec![];
+        for table in self.tables.iter() {
+            for cell in table.cells.values() {
+                if !styles.contains(&&cell.style) {
+                    styles.push(&cell.style);
+                }
+            }
+        }
+        let mut xml = String::from(CONTENT_HEADER);
+        xml.push_str("<office:automatic-styles>\n");
+        for (idx, style) in styles.iter().enumerate() {
+            xml.push_str(&cell_style_xml(&format!("ce{}", idx + 1), style));
+        }
+        for (table_idx, table) in self.tables.iter().enumerate() {
+            for (x, width) in table.column_widths.iter() {
+                // the column width of excel is the number of the characters of 7px
+                xml.push_str(&format!(
+                    "<style:style style:name=\"co{}-{}\" style:family=\"table-column\"><style:table-column-properties style:column-width=\"{:.3}in\"/></style:style>\n",
+                    table_idx + 1,
+                    x,
+                    (width * 7.0 + 5.0) / 96.0
+                ));
+            }
+            for (y, height) in table.row_heights.iter() {
+                xml.push_str(&format!(
+                    "<style:style style:name=\"ro{}-{}\" style:family=\"table-row\"><style:table-row-properties style:row-height=\"{:.2}pt\" style:use-optimal-row-height=\"false\"/></style:style>\n",
+                    table_idx + 1,
+                    y,
+                    height
+                ));
+            }
+        }
+        xml.push_str("</office:automatic-styles>\n");
+        xml.push_str("<office:body>\n<office:spreadsheet>\n");
+        for (table_idx, table) in self.tables.iter().enumerate() {
+            xml.push_str(&table.to_xml(table_idx + 1, &styles));
+        }
+        xml.push_str("</office:spreadsheet>\n</office:body>\n</office:document-content>\n");
+        xml
+    }
+}
+
+/// A sheet of the spreadsheet.
+#[derive(Debug)]
+pub struct Table {
+    name: String,
+    cells: BTreeMap<(u32, u16), Cell>,
+    /// cells hidden by the merged cells
+    covered: HashSet<(u32, u16)>,
+    column_widths: BTreeMap<u16, f64>,
+    row_heights: BTreeMap<u32, f64>,
+}
+
+#[derive(Debug)]
+struct Cell {
+    text: String,
+    style: CellStyle,
+    /// the number of the merged rows and columns
+    span: (u32, u16),
+}
+
+impl Table {
+    fn new(name: &str) -> Self {
+        Table {
+            name: name.to_string(),
+            cells: BTreeMap::new(),
+            covered: HashSet::new(),
+            column_widths: BTreeMap::new(),
+            row_heights: BTreeMap::new(),
+        }
+    }
+
+    pub fn write_string(&mut self, y: u32, x: u16, text: &str, style: &CellStyle) {
+        self.cells.insert(
+            (y, x),
+            Cell {
+                text: text.to_string(),
+                style: style.clone(),
+                span: (1, 1),
+            },
+        );
+    }
+
+    /// Merges the cells from `(first_y, first_x)` to `(last_y, last_x)` and writes the text into it.
+    pub fn merge_range(
+        &mut self,
+        first_y: u32,
+        first_x: u16,
+        last_y: u32,
+        last_x: u16,
+        text: &str,
+        style: &CellStyle,
+    ) {
+        for y in first_y..=last_y {
+            for x in first_x..=last_x {
+                if (y, x) != (first_y, first_x) {
+                    self.covered.insert((y, x));
+                }
+            }
+        }
+        self.cells.insert(
+            (first_y, first_x),
+            Cell {
+                text: text.to_string(),
+                style: style.clone(),
+                span: (last_y - first_y + 1, last_x - first_x + 1),
+            },
+        );
+    }
+
+    /// Sets the width of the column in the number of the characters like excel.
+    pub fn set_column_width(&mut self, x: u16, width: f64) {
+        self.column_widths.insert(x, width);
+    }
+
+    /// Sets the height of the row in points.
+    pub fn set_row_height(&mut self, y: u32, height: f64) {
+        self.row_heights.insert(y, height);
+    }
+
+    fn to_xml(&self, table_idx: usize, styles: &[&CellStyle]) -> String {
+        let positions = self.cells.keys().chain(self.covered.iter());
+        let last_y = positions
+            .clone()
+            .map(|(y, _)| *y)
+            .chain(self.row_heights.keys().copied())
+            .max()
+            .unwrap_or(0);
+        let last_x = positions
+            .map(|(_, x)| *x)
+            .chain(self.column_widths.keys().copied())
+            .max()
+            .unwrap_or(0);
+        let mut xml = format!("<table:table table:name=\"{}\">\n", escape_html(&self.name));
+        for x in 0..=last_x {
+            if self.column_widths.contains_key(&x) {
+                xml.push_str(&format!(
+                    "<table:table-column table:style-name=\"co{}-{}\"/>\n",
+                    table_idx, x
+                ));
+            } else {
+                xml.push_str("<table:table-column/>\n");
+            }
+        }
+        for y in 0..=last_y {
+            if self.row_heights.contains_key(&y) {
+                xml.push_str(&format!(
+                    "<table:table-row table:style-name=\"ro{}-{}\">",
+                    table_idx, y
+                ));
+            } else {
+                xml.push_str("<table:table-row>");
+            }
+            for x in 0..=last_x {
+                if self.covered.contains(&(y, x)) {
+                    xml.push_str("<table:covered-table-cell/>");
+                    continue;
+                }
+                let cell = match self.cells.get(&(y, x)) {
+                    Some(cell) => cell,
+                    None => {
+                        xml.push_str("<table:table-cell/>");
+                        continue;
+                    }
+                };
+                let style_idx = styles.iter().position(|s| **s == cell.style).unwrap_or(0);
+                xml.push_str(&format!(
+                    "<table:table-cell table:style-name=\"ce{}\" office:value-type=\"string\"",
+                    style_idx + 1
+                ));
+                if cell.span.0 > 1 {
+                    xml.push_str(&format!(" table:number-rows-spanned=\"{}\"", cell.span.0));
+                }
+                if cell.span.1 > 1 {
+                    xml.push_str(&format!(
+                        " table:number-columns-spanned=\"{}\"",
+                        cell.span.1
+                    ));
+                }
+                xml.push('>');
+                for line in cell.text.split('\n') {
+                    xml.push_str(&format!("<text:p>{}</text:p>", escape_text(line)));
+                }
+                xml.push_str("</table:table-cell>");
+            }
+            xml.push_str("</table:table-row>\n");
+        }
+        xml.push_str("</table:table>\n");
+        xml
+    }
+}
+
+/// Escapes the text in a paragraph.
+/// Consecutive spaces and tabs are collapsed in ODF, so they're written as the elements.
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::new();
+    let mut spaces = 0;
+    for (idx, c) in text.chars().enumerate() {
+        if c == ' ' && spaces > 0 {
+            spaces += 1;
+            continue;
+        }
+        if spaces > 1 {
+            escaped.push_str(&format!("<text:s text:c=\"{}\"/>", spaces - 1));
+        }
+        spaces = 0;
+        match c {
+            // leading spaces are also collapsed
+            ' ' if idx == 0 => escaped.push_str("<text:s/>"),
+            ' ' => {
+                escaped.push(' ');
+                spaces = 1;
+            }
+            '\t' => escaped.push_str("<text:tab/>"),
+            c => escaped.push_str(&escape_html(&c.to_string())),
+        }
+    }
+    if spaces > 1 {
+        escaped.push_str(&format!("<text:s text:c=\"{}\"/>", spaces - 1));
+    }
+    escaped
+}
+
+fn cell_style_xml(name: &str, style: &CellStyle) -> String {
+    let mut cell = vec![];
+    if let Some(background) = style.background {
+        cell.push(format!("fo:background-color=\"#{:06X}\"", background));
+    }
+    if let Some(border) = style.border {
+        let border = match border {
+            Border::None => String::from("none"),
+            border => {
+                let (width, line) = match border {
+                    Border::Medium => ("1.5pt", "solid"),
+                    Border::Thick => ("2.25pt", "solid"),
+                    Border::Dashed => ("0.75pt", "dashed"),
+                    Border::Dotted => ("0.75pt", "dotted"),
+                    Border::Double => ("2.25pt", "double"),
+                    Border::Hair => ("0.35pt", "solid"),
+                    _ => ("0.75pt", "solid"),
+                };
+                format!(
+                    "{} {} #{:06X}",
+                    width,
+                    line,
+                    style.border_color.unwrap_or(0)
+                )
+            }
+        };
+        cell.push(format!("fo:border=\"{}\"", border));
+    }
+    if style.wrap == Some(true) {
+        cell.push(String::from("fo:wrap-option=\"wrap\""));
+    }
+    if let Some(valign) = style.valign {
+        cell.push(format!(
+            "style:vertical-align=\"{}\"",
+            match valign {
+                VerticalAlign::Top => "top",
+                VerticalAlign::Center => "middle",
+                VerticalAlign::Bottom => "bottom",
+            }
+        ));
+    }
+    let mut text = vec![];
+    if let Some(font) = &style.font {
+        text.push(format!("fo:font-family=\"{}\"", escape_html(font)));
+    }
+    if let Some(size) = style.size {
+        text.push(format!("fo:font-size=\"{}pt\"", size));
+    }
+    if style.bold == Some(true) {
+        text.push(String::from("fo:font-weight=\"bold\""));
+    }
+    if style.italic == Some(true) {
+        text.push(String::from("fo:font-style=\"italic\""));
+    }
+    if let Some(color) = style.color {
+        text.push(format!("fo:color=\"#{:06X}\"", color));
+    }
+    let mut xml = format!(
+        "<style:style style:name=\"{}\" style:family=\"table-cell\">",
+        name
+    );
+    if !cell.is_empty() {
+        xml.push_str(&format!(
+            "<style:table-cell-properties {}/>",
+            cell.join(" ")
+        ));
+    }
+    if let Some(align) = style.align {
+        xml.push_str(&format!(
+            "<style:paragraph-properties fo:text-align=\"{}\"/>",
+            match align {
+                Align::Left => "start",
+                // a single cell can not be centered across the others
+                Align::Center | Align::CenterAcross => "center",
+                Align::Right => "end",
+                Align::Justify => "justify",
+            }
+        ));
+    }
+    if !text.is_empty() {
+        xml.push_str(&format!("<style:text-properties {}/>", text.join(" ")));
+    }
+    xml.push_str("</style:style>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use calamine::{open_workbook, Ods, Reader};
+
+    use super::*;
+
+    use crate::style::Styles;
+
+    #[test]
+    fn test_escape_text() {
+        assert_eq!("a b", escape_text("a b"));
+        assert_eq!("a <text:s text:c=\"2\"/>b", escape_text("a   b"));
+        assert_eq!("<text:s/> a", escape_text("  a"));
+        assert_eq!("a<text:tab/>&lt;b&gt;", escape_text("a\t<b>"));
+    }
+
+    #[test]
+    fn test_cell_style_xml() {
+        let style = Styles::builtin().header;
+        assert_eq!(
+            "<style:style style:name=\"ce1\" style:family=\"table-cell\"><style:table-cell-properties fo:background-color=\"#00FFFF\" fo:border=\"0.75pt solid #000000\" fo:wrap-option=\"wrap\" style:vertical-align=\"middle\"/><style:paragraph-properties fo:text-align=\"center\"/></style:style>\n",
+            cell_style_xml("ce1", &style)
+        );
+        assert_eq!(
+            "<style:style style:name=\"ce2\" style:family=\"table-cell\"></style:style>\n",
+            cell_style_xml("ce2", &CellStyle::default())
+        );
+    }
+
+    #[test]
+    fn test_save() {
+        let path = "unit_test_ods_writer.ods";
+        let mut spreadsheet = Spreadsheet::new(path);
+        let table = spreadsheet.add_table("Sheet & 1");
+        let style = Styles::builtin().body;
+        table.write_string(0, 0, "Title", &Styles::builtin().title);
+        table.merge_range(1, 0, 1, 1, "Group", &style);
+        table.write_string(2, 1, "line 1\nline 2", &style);
+        table.set_column_width(1, 20.0);
+        table.set_row_height(2, 30.0);
+        spreadsheet.add_table("Sheet 2");
+        spreadsheet.save().unwrap();
+        let mut workbook: Ods<_> = open_workbook(path).unwrap();
+        assert_eq!(vec!["Sheet & 1", "Sheet 2"], workbook.sheet_names());
+        let range = workbook.worksheet_range("Sheet & 1").unwrap();
+        assert_eq!(
+            Some(String::from("Title")),
+            range.get_value((0, 0)).map(|cell| cell.to_string())
+        );
+        assert_eq!(
+            Some(String::from("Group")),
+            range.get_value((1, 0)).map(|cell| cell.to_string())
+        );
+        assert_eq!(
+            Some(String::from("line 1\nline 2")),
+            range.get_value((2, 1)).map(|cell| cell.to_string())
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
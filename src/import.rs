@@ -0,0 +1,391 @@
+use std::path::Path;
+
+use anyhow::{anyhow, Result};
+use calamine::{open_workbook, Reader, Xlsx};
+use log::{debug, info};
+
+use crate::rule::{self, Direction, Rule};
+
+/// A worksheet read from the xlsx file.
+/// `rows` holds every cell from `A1` as string.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Worksheet {
+    pub name: String,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Worksheet {
+    fn cell(&self, y: usize, x: usize) -> &str {
+        self.rows
+            .get(y)
+            .and_then(|row| row.get(x))
+            .map(|cell| cell.as_str())
+            .unwrap_or("")
+    }
+
+    fn is_empty_row(&self, y: usize, x: usize, width: usize) -> bool {
+        (x..x + width).all(|x| self.cell(y, x).is_empty())
+    }
+
+    fn width(&self) -> usize {
+        self.rows.iter().map(|row| row.len()).max().unwrap_or(0)
+    }
+}
+
+/// Reads the xlsx file written by `ExcelExporter` and converts it back into Markdown.
+pub fn import_excel(path: &Path, rule: &Rule) -> Result<String> {
+    info!("importing excel file ({:?})...", path);
+    let worksheets = read_excel(path)?;
+    let markdown = to_markdown(&worksheets, rule)?;
+    info!("OK");
+    debug!("imported markdown: \n{}", markdown);
+    Ok(markdown)
+}
+
+fn read_excel(path: &Path) -> Result<Vec<Worksheet>> {
+    let mut workbook: Xlsx<_> =
+        open_workbook(path).map_err(|e| anyhow!("could not open {:?}: {}", path, e))?;
+    let mut worksheets = vec![];
+    for name in workbook.sheet_names() {
+        let range = workbook.worksheet_range(&name)?;
+        let mut rows = vec![];
+        if let Some((end_y, end_x)) = range.end() {
+            for y in 0..=end_y {
+                rows.push(
+                    (0..=end_x)
+                        .map(|x| {
+                            range
+                                .get_value((y, x))
+                                .map(|cell| cell.to_string())
+                                .unwrap_or_default()
+                        })
+                        .collect(),
+                );
+            }
+        }
+        worksheets.push(Worksheet { name, rows });
+    }
+    Ok(worksheets)
+}
+
+/// Converts the worksheets laid out like the `ExcelExporter` output into Markdown.
+/// The block titles are read in the order of the sheet from the origin in the rule,
+/// along the column (or the row with `horizontal` layout), and the body of each block continues until an empty row.
+/// Each block is matched against any block in the rule by its title,
+/// and the block markers are written where the blocks are reordered, repeated or left out.
+/// The cover sheet named in the rule is skipped.
+pub fn to_markdown(worksheets: &[Worksheet], rule: &Rule) -> Result<String> {
+    let (origin_y, origin_x) = rule.layout.origin;
+    let mut sheets = vec![];
+    for worksheet in worksheets.iter() {
+        if rule
+            .cover
+            .as_ref()
+            .is_some_and(|cover| cover.name == worksheet.name)
+        {
+            continue;
+        }
+        // (marker name, lines) of each block
+        let mut blocks: Vec<(Option<&str>, Vec<String>)> = vec![];
+        // the index of the previous block in the rule
+        let mut previous: Option<usize> = None;
+        let (mut y, mut x) = (origin_y as usize, origin_x as usize);
+        loop {
+            // find the next block title
+            let title = match rule.layout.direction {
+                Direction::Vertical => (y..worksheet.rows.len())
+                    .find(|y| !worksheet.cell(*y, x).is_empty())
+                    .map(|title_y| (title_y, x)),
+                Direction::Horizontal => (x..worksheet.width())
+                    .find(|x| !worksheet.cell(y, *x).is_empty())
+                    .map(|title_x| (y, title_x)),
+            };
+            let (title_y, title_x) = match title {
+                Some(title) => title,
+                None => break,
+            };
+            let title = worksheet.cell(title_y, title_x);
+            let rule_idx = rule
+                .doc
+                .blocks
+                .iter()
+                .position(|b| b.title == title)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "block '{}' in sheet '{}' is not defined in the rule.",
+                        title,
+                        worksheet.name
+                    )
+                })?;
+            let b = &rule.doc.blocks[rule_idx];
+            // skip the title and header rows
+            let mut row_y = title_y + if b.merge_info.is_empty() { 2 } else { 3 };
+            let mut lines = vec![];
+            while row_y < worksheet.rows.len()
+                && !worksheet.is_empty_row(row_y, title_x, b.columns.len())
+            {
+                for (offset, column) in b.columns.iter().enumerate() {
+                    let cell = worksheet.cell(row_y, title_x + offset);
+                    if !cell.is_empty() {
+                        if let Some(line) = render_cell(column, cell) {
+                            lines.push(line);
+                        }
+                    }
+                }
+                row_y += 1;
+            }
+            match rule.layout.direction {
+                Direction::Vertical => y = row_y,
+                Direction::Horizontal => x = title_x + b.columns.len(),
+            }
+            // the block without the marker follows the previous one in the rule
+            let name = if previous.map_or(0, |previous| previous + 1) == rule_idx {
+                None
+            } else {
+                Some(b.id.as_deref().unwrap_or(&b.title))
+            };
+            previous = Some(rule_idx);
+            blocks.push((name, lines));
+        }
+        let mut sheet = format!("# {}\n", worksheet.name);
+        for (idx, (name, lines)) in blocks.iter().enumerate() {
+            match (idx, name) {
+                (0, Some(name)) => sheet.push_str(&format!("<!-- block: {} -->\n", name)),
+                (0, None) => {}
+                (_, Some(name)) => sheet.push_str(&format!("--- {}\n", name)),
+                (_, None) => sheet.push_str("---\n"),
+            }
+            if !lines.is_empty() {
+                sheet.push_str(&format!("{}\n", lines.join("\n")));
+            }
+        }
+        sheets.push(sheet);
+    }
+    Ok(sheets.join("\n"))
+}
+
+/// Renders the cell as the Markdown notation defined by the column.
+/// Auto incremented columns are skipped since they're generated again when parsing.
+fn render_cell(column: &rule::Column, cell: &str) -> Option<String> {
+    if column.auto_increment {
+        return None;
+    }
+    let marker = if let Some(prefix) = &column.custom_prefix {
+        prefix.to_string()
+    } else if column.cmark_tag == "List" || column.cmark_tag == "UnorderedList" {
+        String::from("*")
+    } else if column.cmark_tag == "OrderedList" {
+        String::from("1.")
+    } else if let Some(level) = column.cmark_tag.strip_prefix("Heading") {
+        let level = level.parse::<usize>().ok()?;
+        // headings can not have multiple lines
+        return Some(format!(
+            "{} {}",
+            "#".repeat(level),
+            cell.lines().collect::<Vec<&str>>().join(" ")
+        ));
+    } else {
+        return None;
+    };
+    Some(
+        cell.lines()
+            .enumerate()
+            .map(|(idx, line)| {
+                if idx == 0 {
+                    format!("{} {}", marker, line)
+                } else {
+                    format!("{} {}", " ".repeat(marker.chars().count()), line)
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("\n"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use super::*;
+
+    use crate::{data::Data, export::layout::SheetLayout, summary};
+
+    /// Puts the text at the 0-based position of the worksheet.
+    fn put(rows: &mut Vec<Vec<String>>, (y, x): (u32, u16), text: &str) {
+        let (y, x) = (y as usize, x as usize);
+        if rows.len() <= y {
+            rows.resize(y + 1, vec![]);
+        }
+        if rows[y].len() <= x {
+            rows[y].resize(x + 1, String::default());
+        }
+        rows[y][x] = text.to_string();
+    }
+
+    /// Renders the data as the worksheets placed by `SheetLayout`, which `ExcelExporter` writes.
+    /// The merged header is read from its first cell.
+    fn layout(data: &Data) -> Vec<Worksheet> {
+        data.sheets()
+            .iter()
+            .enumerate()
+            .map(|(idx, sheet)| {
+                let sheet_layout = SheetLayout::new(sheet, data.rule()).unwrap();
+                let mut rows = vec![];
+                for block in sheet_layout.blocks.iter() {
+                    put(&mut rows, block.title_pos, block.title);
+                    if let Some(table) = &block.table {
+                        for header in table.headers.iter() {
+                            put(&mut rows, header.first, header.text);
+                        }
+                        for cell in table.cells.iter() {
+                            put(&mut rows, cell.pos, cell.text);
+                        }
+                    }
+                }
+                Worksheet {
+                    name: summary::sheet_name(sheet, idx),
+                    rows,
+                }
+            })
+            .collect()
+    }
+
+    fn assert_round_trip(rule_path: &str, input_path: &str) {
+        let rule = Rule::marshal(&read_to_string(rule_path).unwrap()).unwrap();
+        let data = Data::marshal(&read_to_string(input_path).unwrap(), rule.clone()).unwrap();
+        let markdown = to_markdown(&layout(&data), &rule).unwrap();
+        let imported = Data::marshal(&markdown, rule).unwrap();
+        assert_eq!(data, imported, "{}", markdown);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        assert_round_trip(
+            "test_case/rule/default_rule.yml",
+            "test_case/input/single_block_multi_row.md",
+        );
+        assert_round_trip(
+            "test_case/rule/multi_block.yml",
+            "test_case/input/multi_block_multi_row.md",
+        );
+        assert_round_trip(
+            "test_case/rule/various_list.yml",
+            "test_case/input/various_list_multiple_sheet.md",
+        );
+        assert_round_trip(
+            "test_case/rule/default_rule.yml",
+            "test_case/input/escape_asterisk.md",
+        );
+        assert_round_trip(
+            "test_case/rule/ordered_list.yml",
+            "test_case/input/ordered_list.md",
+        );
+        assert_round_trip(
+            "test_case/rule/word_prefix.yml",
+            "test_case/input/word_prefix.md",
+        );
+        assert_round_trip(
+            "test_case/rule/named_block.yml",
+            "test_case/input/named_block.md",
+        );
+    }
+
+    #[test]
+    fn test_to_markdown_block_marker() {
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/named_block.yml").unwrap()).unwrap();
+        // reordered, repeated and left out blocks
+        let input =
+            "# S1\n<!-- block: Test Cases -->\n## A\n--- pre\n* a\n--- pre\n* b\n\n# S2\n* c\n";
+        let data = Data::marshal(input, rule.clone()).unwrap();
+        assert_eq!(input, to_markdown(&layout(&data), &rule).unwrap());
+        let mut worksheets = layout(&data);
+        worksheets[1].rows[0][0] = String::from("Unknown");
+        assert!(to_markdown(&worksheets, &rule).is_err());
+    }
+
+    #[test]
+    fn test_round_trip_layout() {
+        let mut rule =
+            Rule::marshal(&read_to_string("test_case/rule/layout.yml").unwrap()).unwrap();
+        let input = read_to_string("test_case/input/multi_block_multi_row.md").unwrap();
+        for direction in [Direction::Horizontal, Direction::Vertical].iter() {
+            rule.layout.direction = *direction;
+            let data = Data::marshal(&input, rule.clone()).unwrap();
+            let markdown = to_markdown(&layout(&data), &rule).unwrap();
+            assert_eq!(data, Data::marshal(&markdown, rule.clone()).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_round_trip_cover() {
+        let rule = Rule::marshal(&read_to_string("test_case/rule/cover.yml").unwrap()).unwrap();
+        let data = Data::marshal(
+            &read_to_string("test_case/input/cover.md").unwrap(),
+            rule.clone(),
+        )
+        .unwrap();
+        // the cover sheet written before the sheets of the document
+        let cover = Worksheet {
+            name: String::from("Summary"),
+            rows: vec![
+                vec![String::from("Login Test Report")],
+                vec![],
+                vec![String::from("Version"), String::from("1.2")],
+                vec![String::from("Date"), String::from("2021-04-01")],
+                vec![],
+                vec![
+                    String::from("Sheet"),
+                    String::from("Blocks"),
+                    String::from("Rows"),
+                ],
+            ],
+        };
+        let mut worksheets = vec![cover];
+        worksheets.extend(layout(&data));
+        let markdown = to_markdown(&worksheets, &rule).unwrap();
+        assert!(!markdown.contains("# Summary"), "{}", markdown);
+        assert_eq!(data, Data::marshal(&markdown, rule).unwrap());
+    }
+
+    #[test]
+    fn test_render_cell() {
+        let column = rule::Column {
+            cmark_tag: String::from("Heading3"),
+            ..Default::default()
+        };
+        assert_eq!(
+            Some(String::from("### line 1 line 2")),
+            render_cell(&column, "line 1\nline 2")
+        );
+        let column = rule::Column {
+            cmark_tag: String::from("List"),
+            custom_prefix: Some(String::from("+")),
+            ..Default::default()
+        };
+        assert_eq!(
+            Some(String::from("+ line 1\n  line 2")),
+            render_cell(&column, "line 1\nline 2")
+        );
+        let column = rule::Column {
+            auto_increment: true,
+            ..Default::default()
+        };
+        assert_eq!(None, render_cell(&column, "1"));
+    }
+
+    #[test]
+    fn test_import_excel() {
+        let rule = Rule::marshal(&read_to_string("test_case/rule/general.yml").unwrap()).unwrap();
+        let markdown = import_excel(Path::new("test_case/input/import.xlsx"), &rule).unwrap();
+        assert_eq!(
+            "# Sheet Name\n## Test Variation 1\n* Test Description 1\n  edited in excel\n## Test Variation 2\n* Test Description 2\n",
+            markdown
+        );
+    }
+
+    #[test]
+    fn test_import_excel_not_found() {
+        assert!(import_excel(Path::new("not_found.xlsx"), &Rule::default()).is_err());
+    }
+}
@@ -2,7 +2,16 @@
 
 pub mod app;
 pub mod constant;
+pub mod constraint;
 pub mod data;
+pub mod export;
+pub mod import;
+pub mod lint;
 pub mod mapping;
 pub mod rule;
+pub mod source;
+pub mod style;
+pub mod summary;
 pub mod utils;
+pub mod validator;
+pub mod watcher;
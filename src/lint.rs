@@ -0,0 +1,271 @@
+use std::{collections::HashSet, fmt};
+
+use anyhow::Result;
+use regex::Regex;
+
+use crate::{
+    constraint::Violation,
+    data::Data,
+    rule::{self, Rule, Unmapped},
+    source::ParseError,
+    utils::{get_comment_block_name, is_code_fence},
+};
+
+/// A structural problem found by `lint`.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Issue {
+    /// a problem at a line of the input
+    Source(ParseError),
+    /// a cell violating the constraints of its column, which has no location in the input
+    Row(Violation),
+}
+
+impl Issue {
+    pub fn with_file(self, file: &str) -> Self {
+        match self {
+            Issue::Source(error) => Issue::Source(error.with_file(file)),
+            issue => issue,
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::Source(error) => write!(f, "{}", error),
+            Issue::Row(violation) => write!(f, "{}", violation),
+        }
+    }
+}
+
+/// Parses the input without exporting and returns the structural issues.
+/// The unmapped content is reported as issues even with `unmapped: error` in the rule,
+/// so every issue is collected at once.
+/// Errors which stop parsing are returned as `Err`.
+pub fn lint(input: &str, mut rule: Rule) -> Result<Vec<Issue>> {
+    // the overflow column is kept, since the content put into it is not an issue
+    if rule.general.unmapped == Unmapped::Error {
+        rule.general.unmapped = Unmapped::Warning;
+    }
+    let data = Data::marshal(input, rule)?;
+    let mut issues: Vec<Issue> = data.warnings().iter().cloned().map(Issue::Source).collect();
+    issues.extend(lint_source(input, data.rule()));
+    issues.extend(data.violations().iter().cloned().map(Issue::Row));
+    // issues in the input first, and then the ones in the rows
+    issues.sort_by_key(|issue| match issue {
+        Issue::Source(error) => error.line,
+        Issue::Row(_) => usize::MAX,
+    });
+    Ok(issues)
+}
+
+/// Checks the lines of the input in the same way as `Rule::filter` splits sheets and blocks.
+fn lint_source(input: &str, rule: &Rule) -> Vec<Issue> {
+    let heading = Regex::new(r"^(#{2,8}) ").expect("Invalid regex");
+    // the input is already parsed, so the block markers are valid
+    let selected = rule.select_blocks(input).unwrap_or_default();
+    let mut issues = vec![];
+    let mut sheet_names = HashSet::new();
+    // the sheet name line which has no content yet
+    let mut empty_sheet: Option<(usize, &str)> = None;
+    let mut current_sheet: Option<usize> = None;
+    let mut block_in_sheet = 0;
+    let mut current_block = 0;
+    // the sheet name is Heading 1
+    let mut last_level = 1;
+    let mut in_code_block = false;
+    let lines: Vec<&str> = input
+        .split('\n')
+        .map(|line| line.trim_end_matches('\r'))
+        .collect();
+    for (idx, line) in lines.iter().copied().enumerate() {
+        if is_code_fence(line) {
+            in_code_block = !in_code_block;
+            empty_sheet = None;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix("# ") {
+            if let Some((sheet_idx, sheet_line)) = empty_sheet {
+                issues.push(empty_sheet_issue(sheet_idx, sheet_line));
+            }
+            empty_sheet = Some((idx, line));
+            if !sheet_names.insert(name.trim()) {
+                issues.push(Issue::Source(ParseError::new(
+                    idx,
+                    line,
+                    &format!("duplicate sheet name '{}'.", name.trim()),
+                )));
+            }
+            current_sheet = Some(current_sheet.map_or(0, |sheet| sheet + 1));
+            block_in_sheet = 0;
+            current_block = select(&selected, current_sheet, block_in_sheet);
+            last_level = 1;
+        } else if line.starts_with("---") {
+            block_in_sheet += 1;
+            current_block = select(&selected, current_sheet, block_in_sheet);
+            // the blocks not defined in the rule are reported by `Data::marshal`
+            last_level = 1;
+        } else if !line.trim().is_empty() && get_comment_block_name(line).is_none() {
+            empty_sheet = None;
+            if let Some(captures) = heading.captures(line) {
+                let level = captures[1].len();
+                if let Some(block) = rule.doc.blocks.get(current_block) {
+                    // only the levels mapped to columns are expected
+                    if let Some(skipped) = heading_levels(block)
+                        .into_iter()
+                        .find(|l| last_level < *l && *l < level)
+                    {
+                        issues.push(Issue::Source(ParseError::new(
+                            idx,
+                            line,
+                            &format!(
+                                "heading level {} skips level {} in block '{}'.",
+                                level, skipped, block.title
+                            ),
+                        )));
+                    }
+                }
+                last_level = level;
+            }
+        }
+    }
+    if let Some((sheet_idx, sheet_line)) = empty_sheet {
+        issues.push(empty_sheet_issue(sheet_idx, sheet_line));
+    }
+    issues
+}
+
+/// Returns the index of the block in the rule selected by `Rule::select_blocks`.
+fn select(selected: &[Vec<usize>], sheet_idx: Option<usize>, idx: usize) -> usize {
+    sheet_idx
+        .and_then(|sheet_idx| selected.get(sheet_idx))
+        .and_then(|blocks| blocks.get(idx))
+        .copied()
+        .unwrap_or(idx)
+}
+
+fn empty_sheet_issue(line_idx: usize, line: &str) -> Issue {
+    Issue::Source(ParseError::new(
+        line_idx,
+        line,
+        &format!("sheet '{}' is empty.", line.trim_start_matches('#').trim()),
+    ))
+}
+
+/// Returns the heading levels mapped to the columns in ascending order.
+fn heading_levels(block: &rule::Block) -> Vec<usize> {
+    let mut levels: Vec<usize> = block
+        .columns
+        .iter()
+        .filter(|column| column.custom_prefix.is_none())
+        .filter_map(|column| column.cmark_tag.strip_prefix("Heading"))
+        .filter_map(|level| level.parse().ok())
+        .collect();
+    levels.sort_unstable();
+    levels
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use super::*;
+
+    fn messages(issues: &[Issue]) -> Vec<(Option<usize>, String)> {
+        issues
+            .iter()
+            .map(|issue| match issue {
+                Issue::Source(error) => (Some(error.line), error.message.clone()),
+                Issue::Row(v) => (
+                    None,
+                    format!("{}/{}/{}: {}", v.sheet, v.block, v.row, v.message),
+                ),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_lint_ok() {
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/multi_block.yml").unwrap()).unwrap();
+        let issues = lint(
+            &read_to_string("test_case/input/multi_block_multi_row.md").unwrap(),
+            rule,
+        )
+        .unwrap();
+        assert!(issues.is_empty(), "{:?}", issues);
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/named_block.yml").unwrap()).unwrap();
+        let issues = lint(
+            &read_to_string("test_case/input/named_block.md").unwrap(),
+            rule,
+        )
+        .unwrap();
+        assert!(issues.is_empty(), "{:?}", issues);
+    }
+
+    #[test]
+    fn test_lint() {
+        let rule = Rule::marshal(&read_to_string("test_case/rule/lint.yml").unwrap()).unwrap();
+        let issues = lint(&read_to_string("test_case/input/lint.md").unwrap(), rule).unwrap();
+        assert_eq!(
+            vec![
+                (
+                    Some(3),
+                    String::from("heading level 4 skips level 3 in block 'Block Title 1'.")
+                ),
+                (Some(9), String::from("block 3 is not defined in the rule.")),
+                (Some(12), String::from("sheet 'Sheet Name 2' is empty.")),
+                (
+                    Some(14),
+                    String::from("duplicate sheet name 'Sheet Name 1'.")
+                ),
+                (
+                    None,
+                    String::from(
+                        "Sheet Name 1/Block Title 1/2: column 'Description' is required but empty."
+                    )
+                ),
+            ],
+            messages(&issues)
+        );
+    }
+
+    #[test]
+    fn test_lint_default_unmapped() {
+        // `unmapped: error` by default doesn't stop at the first unmapped content
+        let rule = Rule::marshal(
+            &read_to_string("test_case/rule/lint.yml")
+                .unwrap()
+                .replace("general:\n  unmapped: warning\n", ""),
+        )
+        .unwrap();
+        assert_eq!(Unmapped::Error, rule.general.unmapped);
+        let issues = lint(&read_to_string("test_case/input/lint.md").unwrap(), rule).unwrap();
+        assert_eq!(5, issues.len(), "{:?}", messages(&issues));
+    }
+
+    #[test]
+    fn test_lint_error() {
+        let rule = Rule::marshal(&read_to_string("test_case/rule/lint.yml").unwrap()).unwrap();
+        assert!(lint("## No Sheet Name", rule).is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        let issue = Issue::Row(Violation {
+            sheet: String::from("Sheet"),
+            block: String::from("Block"),
+            row: 2,
+            column: String::from("Column"),
+            message: String::from("something wrong"),
+        });
+        assert_eq!(
+            "something wrong\n --> sheet 'Sheet', block 'Block', row 2",
+            issue.with_file("test.md").to_string()
+        );
+    }
+}
@@ -1,10 +1,10 @@
 use std::collections::HashMap;
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use pulldown_cmark::Tag;
 
 use crate::{
-    constant::AUTO_INCREMENT_KEY,
+    constant::{AUTO_INCREMENT_KEY, OVERFLOW_KEY},
     rule::Rule,
     utils::{cmarktag_stringify, custom_prefix_to_key, get_custom_prefix_key},
 };
@@ -17,31 +17,43 @@ pub struct Mapping {
 impl Mapping {
     pub fn new(rule: &Rule) -> Result<Self> {
         let mut blocks = vec![];
-        rule.doc.blocks.iter().for_each(|block| {
+        for block in rule.doc.blocks.iter() {
             let mut mapping = HashMap::new();
             let mut last_key = None;
-            block.columns.iter().enumerate().for_each(|(idx, column)| {
-                if column.auto_increment {
-                    mapping.insert(AUTO_INCREMENT_KEY.clone(), idx);
+            for (idx, column) in block.columns.iter().enumerate() {
+                let key = if column.auto_increment {
+                    AUTO_INCREMENT_KEY.clone()
+                } else if column.overflow {
+                    OVERFLOW_KEY.clone()
                 } else if let Some(prefix) = &column.custom_prefix {
-                    mapping.insert(get_custom_prefix_key(prefix), idx);
+                    get_custom_prefix_key(prefix)
                 } else {
-                    mapping.insert(column.cmark_tag.clone(), idx);
+                    column.cmark_tag.clone()
+                };
+                // columns without md never get any content
+                if key.is_empty() {
+                    continue;
+                }
+                if let Some(duplicated) = mapping.insert(key.clone(), idx) {
+                    return Err(anyhow!(
+                        "columns '{}' and '{}' in block '{}' are both mapped to '{}'. Use customPrefix, OrderedList or UnorderedList to distinguish them.",
+                        block.columns[duplicated].title,
+                        column.title,
+                        block.title,
+                        key
+                    ));
                 }
                 if column.is_last {
-                    if let Some(prefix) = &column.custom_prefix {
-                        last_key = Some(get_custom_prefix_key(prefix));
-                    } else {
-                        last_key = Some(column.cmark_tag.clone());
-                    }
+                    last_key = Some(key);
                 }
-            });
+            }
             blocks.push(Block {
                 title: block.title.clone(),
                 mapping,
                 last_key,
+                size: block.columns.len(),
             });
-        });
+        }
         Ok(Mapping { blocks })
     }
 }
@@ -66,6 +78,14 @@ impl Mapping {
         None
     }
 
+    /// Returns the index of the column receiving the content which no column is mapped to.
+    pub fn get_overflow_idx(&self, block_idx: usize) -> Option<&usize> {
+        if let Some(block) = self.blocks.get(block_idx) {
+            return block.get_overflow_idx();
+        }
+        None
+    }
+
     pub fn get_size(&self, block_idx: usize) -> Option<usize> {
         if let Some(block) = self.blocks.get(block_idx) {
             return block.get_size();
@@ -104,9 +124,22 @@ struct Block {
     title: String,
     mapping: HashMap<String, usize>,
     last_key: Option<String>,
+    size: usize,
 }
 
 impl Block {
+    /// Returns the key of the tag in the mapping.
+    /// Ordered and unordered lists fall back to `List` if there is no column for them.
+    fn tag_to_key(&self, tag: &Tag<'_>) -> Option<String> {
+        let key = cmarktag_stringify(tag)?;
+        if let Tag::List(_) = tag {
+            if !self.mapping.contains_key(&key) {
+                return Some(String::from("List"));
+            }
+        }
+        Some(key)
+    }
+
     pub fn get_idx(
         &self,
         tag: Option<&Tag<'_>>,
@@ -115,8 +148,8 @@ impl Block {
         if let Some(key) = custom_prefix_to_key(text_with_custom_prefix) {
             return self.mapping.get(&key);
         } else if let Some(t) = tag {
-            if let Some(tag_str) = cmarktag_stringify(t) {
-                return self.mapping.get(&tag_str);
+            if let Some(key) = self.tag_to_key(t) {
+                return self.mapping.get(&key);
             }
         }
         None
@@ -126,8 +159,12 @@ impl Block {
         self.mapping.get(&AUTO_INCREMENT_KEY.clone())
     }
 
+    pub fn get_overflow_idx(&self) -> Option<&usize> {
+        self.mapping.get(&OVERFLOW_KEY.clone())
+    }
+
     pub fn get_size(&self) -> Option<usize> {
-        Some(self.mapping.len())
+        Some(self.size)
     }
 
     pub fn is_last_key(
@@ -138,8 +175,8 @@ impl Block {
         let k = if let Some(key) = custom_prefix_to_key(text_with_custom_prefix) {
             key
         } else if let Some(t) = tag {
-            if let Some(tag_str) = cmarktag_stringify(t) {
-                tag_str
+            if let Some(key) = self.tag_to_key(t) {
+                key
             } else {
                 return false;
             }
@@ -159,6 +196,7 @@ impl Default for Block {
             title: String::default(),
             mapping: HashMap::new(),
             last_key: None,
+            size: 0,
         }
     }
 }
@@ -171,6 +209,7 @@ mod tests {
 
     use crate::{
         constant::AUTO_INCREMENT_KEY,
+        rule,
         utils::{get_custom_prefix_as_normal_list, get_custom_prefix_key},
     };
 
@@ -217,6 +256,7 @@ mod tests {
                 title: String::from("Block Title"),
                 mapping: map,
                 last_key: Some(String::from("List")),
+                size: 9,
             }],
         };
         assert_eq!(expected, mapping);
@@ -246,6 +286,7 @@ mod tests {
                 title: String::from("Block Title 1"),
                 mapping: map,
                 last_key: Some(get_custom_prefix_key("$")),
+                size: 11,
             }],
         };
         let mut map = HashMap::new();
@@ -257,6 +298,7 @@ mod tests {
             title: String::from("Block Title 2"),
             mapping: map,
             last_key: Some(get_custom_prefix_key("+")),
+            size: 4,
         });
         assert_eq!(expected, mapping);
         assert!(!mapping.is_last_key(0, Some(&Tag::Heading(8)), None));
@@ -270,4 +312,63 @@ mod tests {
             )
         ));
     }
+
+    #[test]
+    fn test_mapping_ordered_list() {
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/ordered_list.yml").unwrap()).unwrap();
+        let mapping = Mapping::new(&rule).unwrap();
+        assert_eq!(
+            Some(&2),
+            mapping.get_idx(0, Some(&Tag::List(Some(1))), None)
+        );
+        assert_eq!(Some(&3), mapping.get_idx(0, Some(&Tag::List(None)), None));
+        assert!(mapping.is_last_key(0, Some(&Tag::List(None)), None));
+        assert!(!mapping.is_last_key(0, Some(&Tag::List(Some(1))), None));
+        // both of them fall back to `List`
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/default_rule.yml").unwrap()).unwrap();
+        let mapping = Mapping::new(&rule).unwrap();
+        assert_eq!(
+            Some(&8),
+            mapping.get_idx(0, Some(&Tag::List(Some(1))), None)
+        );
+        assert_eq!(Some(&8), mapping.get_idx(0, Some(&Tag::List(None)), None));
+    }
+
+    #[test]
+    fn test_mapping_ambiguous() {
+        let mut rule =
+            Rule::marshal(&read_to_string("test_case/rule/default_rule.yml").unwrap()).unwrap();
+        let mut column = rule.doc.blocks[0].columns[8].clone();
+        column.title = String::from("Another Description");
+        rule.doc.blocks[0].columns.push(column);
+        assert!(Mapping::new(&rule).is_err());
+    }
+
+    #[test]
+    fn test_mapping_column_without_md() {
+        let mut rule =
+            Rule::marshal(&read_to_string("test_case/rule/default_rule.yml").unwrap()).unwrap();
+        for title in ["Status", "Note"].iter() {
+            rule.doc.blocks[0].columns.push(rule::Column {
+                title: title.to_string(),
+                ..Default::default()
+            });
+        }
+        let mapping = Mapping::new(&rule).unwrap();
+        assert_eq!(Some(11), mapping.get_size(0));
+    }
+
+    #[test]
+    fn test_mapping_overflow() {
+        let rule = Rule::marshal(&read_to_string("test_case/rule/overflow.yml").unwrap()).unwrap();
+        let mapping = Mapping::new(&rule).unwrap();
+        assert_eq!(Some(&3), mapping.get_overflow_idx(0));
+        assert_eq!(Some(&2), mapping.get_overflow_idx(1));
+        assert!(mapping.get_overflow_idx(2).is_none());
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/default_rule.yml").unwrap()).unwrap();
+        assert!(Mapping::new(&rule).unwrap().get_overflow_idx(0).is_none());
+    }
 }
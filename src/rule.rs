@@ -5,7 +5,18 @@ use log::{debug, info};
 use regex::Regex;
 use yaml_rust::{Yaml, YamlLoader};
 
-use crate::utils::get_custom_prefix_as_normal_list;
+use crate::{
+    source::ParseError,
+    style::{Styles, ValueStyle},
+    utils::{
+        get_comment_block_name, get_custom_prefix_as_normal_list, get_separator_block_name,
+        is_code_fence, parse_cell_ref,
+    },
+    validator::validate,
+};
+
+/// prepended to the lines in code blocks while filtering
+const CODE_BLOCK_MARKER: &str = "!!!CODEBLOCK";
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct MergeInfo {
@@ -26,16 +37,36 @@ impl MergeInfo {
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Rule {
+    pub general: General,
+    pub layout: Layout,
+    /// styles of the whole document in the excel output
+    pub style: Styles,
+    /// the cover sheet prepended to the excel output
+    pub cover: Option<Cover>,
     pub doc: Doc,
 }
 
 impl Rule {
     pub fn marshal(input: &str) -> Result<Self> {
         info!("parsing rules...");
+        let diagnostics = validate(input)?;
+        if !diagnostics.is_empty() {
+            return Err(anyhow::anyhow!(
+                "rule file is invalid:\n{}",
+                diagnostics
+                    .iter()
+                    .map(|d| format!("  {}", d))
+                    .collect::<Vec<String>>()
+                    .join("\n")
+            ));
+        }
         let docs = YamlLoader::load_from_str(input)?;
+        let general = General::marshal(&docs[0]["general"]);
+        let layout = Layout::marshal(&docs[0]["layout"]);
+        let style = Styles::marshal(&docs[0]["style"], "style")?;
+        let cover = Cover::marshal(&docs[0]["cover"]);
         let doc = &docs[0]["doc"];
         let mut blcs = vec![];
-        // TODO: validation
 
         if let Some(blocks) = doc["blocks"].as_vec() {
             for v in blocks.iter() {
@@ -43,6 +74,9 @@ impl Rule {
                 if let Some(title) = v["title"].as_str() {
                     blc.title = title.to_string();
                 }
+                blc.id = v["id"].as_str().map(String::from);
+                blc.style = Styles::marshal(&v["style"], "block style")?;
+                blc.autofilter = v["autofilter"].as_bool().unwrap_or(false);
                 if let Some(block) = v["content"].as_vec() {
                     let mut idx: usize = 0;
                     let mut group_from: Option<usize> = None;
@@ -103,26 +137,55 @@ impl Rule {
                                             .as_str()
                                             .unwrap(),
                                     ),
-                                    custom_prefix: {
-                                        if let Some(prefix) =
-                                            clm.get(&Yaml::String("customPrefix".to_string()))
-                                        {
-                                            //Some(prefix.as_str().unwrap_or("").to_string())
-                                            let p: Result<&str> = if let Some(p) = prefix.as_str() {
-                                                if p.len() != 1 {
-                                                    return Err(anyhow::anyhow!("Custom prefix's length must be 1. Your input is {}", p.len()));
-                                                }
-                                                Ok(p)
-                                            } else {
-                                                return Err(anyhow::anyhow!("Custom prefix is malformed. It could not be converted into string: {:?}", prefix));
-                                            };
-                                            Some(p?.to_string())
-                                        } else {
-                                            None
-                                        }
-                                    },
+                                    custom_prefix: clm
+                                        .get(&Yaml::String("customPrefix".to_string()))
+                                        .and_then(|prefix| prefix.as_str())
+                                        .map(String::from),
                                     group: group.as_ref().cloned(),
                                     is_last: i == block.len().saturating_sub(1),
+                                    overflow: clm
+                                        .get(&Yaml::String("overflow".to_string()))
+                                        .and_then(|overflow| overflow.as_bool())
+                                        .unwrap_or(false),
+                                    required: clm
+                                        .get(&Yaml::String("required".to_string()))
+                                        .and_then(|required| required.as_bool())
+                                        .unwrap_or(false),
+                                    max_length: clm
+                                        .get(&Yaml::String("maxLength".to_string()))
+                                        .and_then(|max_length| max_length.as_i64())
+                                        .map(|max_length| max_length as usize),
+                                    pattern: clm
+                                        .get(&Yaml::String("pattern".to_string()))
+                                        .and_then(|pattern| pattern.as_str())
+                                        .map(String::from),
+                                    enum_values: clm
+                                        .get(&Yaml::String("enum".to_string()))
+                                        .and_then(|values| values.as_vec())
+                                        .map(|values| {
+                                            values.iter().filter_map(scalar_to_string).collect()
+                                        }),
+                                    dropdown: clm
+                                        .get(&Yaml::String("dropdown".to_string()))
+                                        .and_then(|dropdown| dropdown.as_bool())
+                                        .unwrap_or(false),
+                                    value_styles: ValueStyle::marshal_list(
+                                        clm.get(&Yaml::String("valueStyles".to_string()))
+                                            .unwrap_or(&Yaml::BadValue),
+                                        "valueStyles",
+                                    )?,
+                                    width: clm.get(&Yaml::String("width".to_string())).and_then(
+                                        |width| match width {
+                                            Yaml::Integer(width) => Some(*width as f64),
+                                            width => width.as_f64(),
+                                        },
+                                    ),
+                                    style: Styles::marshal(
+                                        clm.get(&Yaml::String("style".to_string()))
+                                            // allows key style to be undefined
+                                            .unwrap_or(&Yaml::BadValue),
+                                        "column style",
+                                    )?,
                                 });
                             }
                             if let Some(g) = &group {
@@ -139,6 +202,10 @@ impl Rule {
             }
         }
         let rule = Rule {
+            general,
+            layout,
+            style,
+            cover,
             doc: Doc { blocks: blcs },
         };
 
@@ -147,10 +214,116 @@ impl Rule {
         Ok(rule)
     }
 
+    /// Returns the index of the block whose id or title is `name`.
+    /// Ids are looked up first.
+    pub fn find_block(&self, name: &str) -> Option<usize> {
+        let blocks = &self.doc.blocks;
+        blocks
+            .iter()
+            .position(|block| block.id.as_deref() == Some(name))
+            .or_else(|| blocks.iter().position(|block| block.title == name))
+    }
+
+    /// Selects the block in the rule for every block of every sheet in the input.
+    /// A block is selected by the name in its marker, which is either the separator `--- <name>`
+    /// or `<!-- block: <name> -->` on its first line.
+    /// A block without a marker follows the previous one in the rule (the first one at the top of a sheet).
+    /// Returns the indexes of the selected blocks in the rule for each sheet.
+    pub fn select_blocks(&self, input: &str) -> Result<Vec<Vec<usize>>> {
+        let mut sheets: Vec<Vec<usize>> = vec![];
+        // the block waiting for its first line, and its marker if the separator has the name
+        let mut pending: Option<Option<(usize, &str, &str)>> = None;
+        let mut in_code_block = false;
+        for (idx, line) in input.split('\n').enumerate() {
+            let line = line.trim_end_matches('\r');
+            let is_code = in_code_block || is_code_fence(line);
+            if is_code_fence(line) {
+                in_code_block = !in_code_block;
+            }
+            if is_code {
+                if let (Some(marker), Some(blocks)) = (pending.take(), sheets.last_mut()) {
+                    self.select_block(blocks, marker)?;
+                }
+            } else if line.starts_with("# ") {
+                if let (Some(marker), Some(blocks)) = (pending.take(), sheets.last_mut()) {
+                    self.select_block(blocks, marker)?;
+                }
+                sheets.push(vec![]);
+                pending = Some(None);
+            } else if line.starts_with("---") {
+                if let (Some(marker), Some(blocks)) = (pending.take(), sheets.last_mut()) {
+                    self.select_block(blocks, marker)?;
+                }
+                pending = Some(get_separator_block_name(line).map(|name| (idx, line, name)));
+            } else if !line.trim().is_empty() {
+                if let (Some(marker), Some(blocks)) = (pending.take(), sheets.last_mut()) {
+                    let marker = marker
+                        .or_else(|| get_comment_block_name(line).map(|name| (idx, line, name)));
+                    self.select_block(blocks, marker)?;
+                }
+            }
+        }
+        if let (Some(marker), Some(blocks)) = (pending, sheets.last_mut()) {
+            self.select_block(blocks, marker)?;
+        }
+        Ok(sheets)
+    }
+
+    /// Pushes the index of the block selected by the marker (line index, line, name) to `blocks`.
+    fn select_block(
+        &self,
+        blocks: &mut Vec<usize>,
+        marker: Option<(usize, &str, &str)>,
+    ) -> Result<()> {
+        let selected = match marker {
+            Some((idx, line, name)) => self.find_block(name).ok_or_else(|| {
+                // points to the name, which is the last one in the marker
+                ParseError::at(
+                    idx,
+                    line,
+                    line.rfind(name).unwrap_or(0),
+                    &format!(
+                        "block '{}' is not defined in the rule (expected one of: {}).",
+                        name,
+                        self.doc
+                            .blocks
+                            .iter()
+                            .map(|block| block.id.as_deref().unwrap_or(&block.title))
+                            .collect::<Vec<&str>>()
+                            .join(", ")
+                    ),
+                )
+            })?,
+            None => blocks.last().map_or(0, |previous| previous + 1),
+        };
+        blocks.push(selected);
+        Ok(())
+    }
+
     /// This function filters the custom prefix lists into normal lists.
     /// In addition, it prepends `!!!CUSTOMPREFIX<prefix>` to be able to be checked if they're custom prefix lists or not.
     /// For example: `+ hogehoge` -> `* !!!CUSTOMPREFIX+ hogehoge`
     pub fn filter(&self, input: &str) -> String {
+        // errors of the block markers are reported by `Data::marshal`
+        let selected = self.select_blocks(input).unwrap_or_default();
+        // hide the lines in code blocks from the separators and the custom prefixes below
+        let mut in_code_block = false;
+        let masked: Vec<String> = input
+            .split('\n')
+            .map(|line| {
+                let is_code = in_code_block || is_code_fence(line);
+                if is_code_fence(line) {
+                    in_code_block = !in_code_block;
+                }
+                if is_code {
+                    format!("{}{}", CODE_BLOCK_MARKER, line)
+                } else {
+                    line.to_string()
+                }
+            })
+            .collect();
+        let input = masked.join("\n");
+        let input = input.as_str();
         let sheet_separator = Regex::new(r"(?m)^# (.*)").expect("Invalid regex");
         let separator = Regex::new(r"(?m)^---(.*)").expect("Invalid regex");
         let headers: Vec<&str> = sheet_separator
@@ -158,11 +331,18 @@ impl Rule {
             .map(|c| c.get(0).unwrap().as_str())
             .collect();
         let mut result = vec![];
-        for sheet in sheet_separator.split(input) {
+        for (sheet_idx, sheet) in sheet_separator.split(input).enumerate() {
             let mut sheet_result = vec![];
             for (idx, block) in separator.split(sheet).enumerate() {
                 let mut block_replaced = block.to_owned();
-                if let Some(b) = self.doc.blocks.get(idx) {
+                // the text before the first sheet name has no sheet
+                let block_idx = sheet_idx
+                    .checked_sub(1)
+                    .and_then(|sheet_idx| selected.get(sheet_idx))
+                    .and_then(|blocks| blocks.get(idx))
+                    .copied()
+                    .unwrap_or(idx);
+                if let Some(b) = self.doc.blocks.get(block_idx) {
                     for column in b.columns.iter() {
                         if let Some(prefix) = &column.custom_prefix {
                             let mut lines = vec![];
@@ -215,18 +395,174 @@ impl Rule {
             }
             i += 2;
         }
-        r
+        r.split('\n')
+            .map(|line| line.strip_prefix(CODE_BLOCK_MARKER).unwrap_or(line))
+            .collect::<Vec<&str>>()
+            .join("\n")
     }
 }
 
 impl Default for Rule {
     fn default() -> Self {
         Rule {
+            general: General::default(),
+            layout: Layout::default(),
+            style: Styles::default(),
+            cover: None,
             doc: Doc::default(),
         }
     }
 }
 
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct General {
+    pub copyright: Option<String>,
+    pub prefix: Option<String>,
+    pub unmapped: Unmapped,
+    /// highlights the cells violating the column constraints in the excel output
+    pub highlight: bool,
+    /// title of the document shown in the cover sheet
+    pub title: Option<String>,
+    pub version: Option<String>,
+    pub date: Option<String>,
+}
+
+impl General {
+    /// Reads the general section, whose values are checked by `validate` before.
+    fn marshal(general: &Yaml) -> Self {
+        let get_str = |key: &str| general[key].as_str().map(String::from);
+        General {
+            copyright: get_str("copyright"),
+            prefix: get_str("prefix"),
+            unmapped: match general["unmapped"].as_str() {
+                Some("warning") => Unmapped::Warning,
+                Some("overflow") => Unmapped::Overflow,
+                _ => Unmapped::Error,
+            },
+            highlight: general["highlight"].as_bool().unwrap_or(false),
+            title: get_str("title"),
+            // allows versions such as `1.0`
+            version: scalar_to_string(&general["version"]),
+            date: get_str("date"),
+        }
+    }
+
+    /// Returns the id of the auto incremented column.
+    /// If `prefix` is defined, the id is formatted like `IT-001`.
+    pub fn format_id(&self, num: usize) -> String {
+        if let Some(prefix) = &self.prefix {
+            format!("{}-{:03}", prefix, num)
+        } else {
+            format!("{}", num)
+        }
+    }
+}
+
+/// The cover sheet listing the statistics of every sheet.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Cover {
+    /// name of the cover sheet
+    pub name: String,
+    /// title of the column whose values are counted in each sheet
+    pub count_by: Option<String>,
+}
+
+impl Cover {
+    /// `cover: true` enables the cover sheet with the default settings.
+    /// The values are checked by `validate` before.
+    fn marshal(cover: &Yaml) -> Option<Self> {
+        match cover {
+            Yaml::Boolean(true) => Some(Cover::default()),
+            Yaml::Hash(_) => {
+                let default = Cover::default();
+                Some(Cover {
+                    name: cover["name"].as_str().map_or(default.name, String::from),
+                    count_by: cover["countBy"].as_str().map(String::from),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Default for Cover {
+    fn default() -> Self {
+        Cover {
+            name: String::from("Cover"),
+            count_by: None,
+        }
+    }
+}
+
+/// How to handle the content which no column is mapped to,
+/// such as a heading level or a list type the block doesn't have.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum Unmapped {
+    /// stops parsing and reports the location
+    #[default]
+    Error,
+    /// skips the content and reports the location as a warning
+    Warning,
+    /// puts the content into the column with `overflow: true`
+    Overflow,
+}
+
+/// Where the blocks are placed in the excel output.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Layout {
+    /// 0-based row and column of the cell where the first block starts
+    pub origin: (u32, u16),
+    /// number of empty rows (or columns with `horizontal`) between blocks
+    pub gap: u32,
+    pub direction: Direction,
+    /// estimates the column widths and the row heights from the cell text
+    pub auto_fit: bool,
+    /// freezes the rows above the body of the first block in each sheet
+    pub freeze: bool,
+}
+
+impl Layout {
+    /// Reads the layout section, whose values are checked by `validate` before.
+    fn marshal(layout: &Yaml) -> Self {
+        let default = Layout::default();
+        Layout {
+            origin: layout["origin"]
+                .as_str()
+                .and_then(parse_cell_ref)
+                .unwrap_or(default.origin),
+            gap: layout["gap"].as_i64().map_or(default.gap, |gap| gap as u32),
+            direction: match layout["direction"].as_str() {
+                Some("horizontal") => Direction::Horizontal,
+                _ => Direction::Vertical,
+            },
+            auto_fit: layout["autoFit"].as_bool().unwrap_or(default.auto_fit),
+            freeze: layout["freeze"].as_bool().unwrap_or(default.freeze),
+        }
+    }
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout {
+            origin: (0, 0),
+            gap: 1,
+            direction: Direction::default(),
+            auto_fit: false,
+            freeze: false,
+        }
+    }
+}
+
+/// How the blocks in a sheet are stacked.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum Direction {
+    /// from top to bottom
+    #[default]
+    Vertical,
+    /// from left to right
+    Horizontal,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct Doc {
     pub blocks: Vec<Block>,
@@ -241,6 +577,12 @@ impl Default for Doc {
 #[derive(Debug, PartialEq, Clone)]
 pub struct Block {
     pub title: String,
+    /// name to select the block with the block marker besides the title
+    pub id: Option<String>,
+    /// styles of the block, overriding the ones of the document
+    pub style: Styles,
+    /// adds the autofilter to the header in the excel output
+    pub autofilter: bool,
     pub columns: Vec<Column>,
     pub merge_info: Vec<MergeInfo>,
 }
@@ -249,6 +591,9 @@ impl Default for Block {
     fn default() -> Self {
         Block {
             title: String::default(),
+            id: None,
+            style: Styles::default(),
+            autofilter: false,
             columns: vec![],
             merge_info: vec![],
         }
@@ -263,6 +608,24 @@ pub struct Column {
     pub custom_prefix: Option<String>,
     pub group: Option<Rc<Group>>,
     pub is_last: bool,
+    /// receives the content which no column is mapped to
+    pub overflow: bool,
+    /// every row must have the content in this column
+    pub required: bool,
+    /// maximum number of characters in the cell
+    pub max_length: Option<usize>,
+    /// regular expression the whole cell must match
+    pub pattern: Option<String>,
+    /// values allowed in the cell
+    pub enum_values: Option<Vec<String>>,
+    /// adds the drop-down list of `enum_values` to the body cells in the excel output
+    pub dropdown: bool,
+    /// styles applied to the body cells matching the values in the excel and ods output
+    pub value_styles: Vec<ValueStyle>,
+    /// width of the column in the excel output, in characters
+    pub width: Option<f64>,
+    /// styles of the header and the body cells, overriding the ones of the block
+    pub style: Styles,
 }
 
 impl Default for Column {
@@ -274,6 +637,15 @@ impl Default for Column {
             custom_prefix: None,
             group: None,
             is_last: false,
+            overflow: false,
+            required: false,
+            max_length: None,
+            pattern: None,
+            enum_values: None,
+            dropdown: false,
+            value_styles: vec![],
+            width: None,
+            style: Styles::default(),
         }
     }
 }
@@ -283,16 +655,31 @@ pub struct Group {
     pub title: String,
 }
 
+/// Converts the scalar value such as `enum: [1, 2]` into string.
+pub(crate) fn scalar_to_string(value: &Yaml) -> Option<String> {
+    match value {
+        Yaml::String(s) | Yaml::Real(s) => Some(s.clone()),
+        Yaml::Integer(i) => Some(i.to_string()),
+        Yaml::Boolean(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs::read_to_string;
 
     use super::*;
+    use crate::style::{Align, Border, CellStyle, VerticalAlign};
 
     #[test]
     fn test_default_rule() {
         let rule = Rule::default();
         let expected = Rule {
+            general: General::default(),
+            layout: Layout::default(),
+            style: Styles::default(),
+            cover: None,
             doc: Doc::default(),
         };
         assert_eq!(expected, rule);
@@ -312,9 +699,16 @@ mod tests {
             title: String::from("Variation"),
         });
         let expected = Rule {
+            general: General::default(),
+            layout: Layout::default(),
+            style: Styles::default(),
+            cover: None,
             doc: Doc {
                 blocks: vec![Block {
                     title: String::from("Block Title"),
+                    id: None,
+                    style: Styles::default(),
+                    autofilter: false,
                     columns: vec![
                         Column {
                             title: String::from("No"),
@@ -389,10 +783,17 @@ mod tests {
             title: String::from("Variation"),
         });
         let expected = Rule {
+            general: General::default(),
+            layout: Layout::default(),
+            style: Styles::default(),
+            cover: None,
             doc: Doc {
                 blocks: vec![
                     Block {
                         title: String::from("Block Title 1"),
+                        id: None,
+                        style: Styles::default(),
+                        autofilter: false,
                         columns: vec![
                             Column {
                                 title: String::from("No"),
@@ -468,6 +869,9 @@ mod tests {
                     },
                     Block {
                         title: String::from("Block Title 2"),
+                        id: None,
+                        style: Styles::default(),
+                        autofilter: false,
                         columns: vec![
                             Column {
                                 title: String::from("No"),
@@ -502,11 +906,251 @@ mod tests {
     }
 
     #[test]
-    fn test_marshal_various_list_prefix_too_long() {
-        assert!(Rule::marshal(
-            &read_to_string("test_case/rule/various_list_prefix_too_long.yml").unwrap(),
+    fn test_marshal_invalid_schema() {
+        let err = Rule::marshal(&read_to_string("test_case/rule/invalid_schema.yml").unwrap())
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("rule file is invalid:\n"));
+        assert!(err.contains("line 13, column 15: unknown md tag 'Heading9'"));
+    }
+
+    #[test]
+    fn test_marshal_ambiguous_list() {
+        let err = Rule::marshal(&read_to_string("test_case/rule/ambiguous_list.yml").unwrap())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains(
+            "line 10, column 13: column 'Expected Result' is mapped to List as well as column 'Procedure'."
+        ));
+    }
+
+    #[test]
+    fn test_marshal_general() {
+        let rule = Rule::marshal(&read_to_string("test_case/rule/general.yml").unwrap()).unwrap();
+        let expected = General {
+            copyright: Some(String::from("Copyright (c) 2021 toolbox-labo")),
+            prefix: Some(String::from("IT")),
+            ..Default::default()
+        };
+        assert_eq!(expected, rule.general);
+    }
+
+    #[test]
+    fn test_marshal_general_malformed() {
+        assert!(Rule::marshal("general: hoge\ndoc:\n  blocks: []").is_err());
+        assert!(Rule::marshal("general:\n  prefix:\n    - IT\ndoc:\n  blocks: []").is_err());
+        assert!(Rule::marshal("general:\n  unmapped: ignore\ndoc:\n  blocks: []").is_err());
+        assert!(Rule::marshal("general:\n  highlight: red\ndoc:\n  blocks: []").is_err());
+    }
+
+    #[test]
+    fn test_marshal_overflow() {
+        let rule = Rule::marshal(&read_to_string("test_case/rule/overflow.yml").unwrap()).unwrap();
+        assert_eq!(Unmapped::Overflow, rule.general.unmapped);
+        assert!(rule.doc.blocks[0].columns[3].overflow);
+        assert!(!rule.doc.blocks[0].columns[2].overflow);
+    }
+
+    #[test]
+    fn test_marshal_constraint() {
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/constraint.yml").unwrap()).unwrap();
+        assert!(rule.general.highlight);
+        let columns = &rule.doc.blocks[0].columns;
+        assert_eq!(Some(20), columns[1].max_length);
+        assert_eq!(
+            Some(vec![
+                String::from("High"),
+                String::from("Middle"),
+                String::from("Low")
+            ]),
+            columns[2].enum_values
+        );
+        assert!(columns[3].required);
+        assert_eq!(Some(String::from("[A-Z]+-[0-9]+")), columns[5].pattern);
+        assert_eq!(None, columns[5].enum_values);
+    }
+
+    #[test]
+    fn test_select_blocks() {
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/named_block.yml").unwrap()).unwrap();
+        assert_eq!(Some(0), rule.find_block("pre"));
+        assert_eq!(Some(0), rule.find_block("Preconditions"));
+        assert_eq!(None, rule.find_block("Postconditions"));
+        assert_eq!(
+            vec![vec![1, 0], vec![0, 1]],
+            rule.select_blocks(&read_to_string("test_case/input/named_block.md").unwrap())
+                .unwrap()
+        );
+        // positional
+        assert_eq!(
+            vec![vec![0, 1, 2]],
+            rule.select_blocks("# Sheet\n---\n\n---").unwrap()
+        );
+        assert_eq!(
+            vec![vec![1, 2]],
+            rule.select_blocks("# Sheet\n\n<!-- block: Test Cases -->\n## Case\n---")
+                .unwrap()
+        );
+        // the comment must be on the first line of the block
+        assert_eq!(
+            vec![vec![0]],
+            rule.select_blocks("# Sheet\n* Condition\n<!-- block: Test Cases -->")
+                .unwrap()
+        );
+        assert!(rule.select_blocks("# Sheet\n--- Notes").is_err());
+        // separators and sheet names in code blocks are not counted
+        assert_eq!(
+            vec![vec![0, 1]],
+            rule.select_blocks("# Sheet\n```\n---\n# Code\n--- Notes\n```\n---")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_filter_named_block() {
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/named_block.yml").unwrap()).unwrap();
+        assert_eq!(
+            "# Sheet\n* Condition\n+ not replaced\n\n---\n* !!!CUSTOMPREFIX+ replaced",
+            rule.filter("# Sheet\n* Condition\n+ not replaced\n--- Test Cases\n+ replaced")
+        );
+    }
+
+    #[test]
+    fn test_filter_code_block() {
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/named_block.yml").unwrap()).unwrap();
+        assert_eq!(
+            "# Sheet\n```\n---\n+ code\n```\n\n---\n* !!!CUSTOMPREFIX+ replaced",
+            rule.filter("# Sheet\n```\n---\n+ code\n```\n---\n+ replaced")
+        );
+    }
+
+    #[test]
+    fn test_marshal_style() {
+        let rule = Rule::marshal(&read_to_string("test_case/rule/style.yml").unwrap()).unwrap();
+        assert_eq!(Some(String::from("Meiryo")), rule.style.title.font);
+        assert_eq!(Some(0x1F4E78), rule.style.header.background);
+        let block = &rule.doc.blocks[0];
+        assert_eq!(Some(0x375623), block.style.header.background);
+        assert_eq!(Some(Align::Center), block.columns[0].style.body.align);
+        assert_eq!(
+            CellStyle {
+                border: Some(Border::Dotted),
+                border_color: Some(0x808080),
+                valign: Some(VerticalAlign::Center),
+                wrap: Some(false),
+                ..Default::default()
+            },
+            block.columns[1].style.body
+        );
+        // each level overrides the upper one
+        let styles = rule
+            .style
+            .merge(&block.style)
+            .merge(&block.columns[1].style);
+        assert_eq!(Some(0x375623), styles.header.background);
+        assert_eq!(Some(Align::Left), styles.header.align);
+        assert_eq!(Some(true), styles.header.bold);
+        assert_eq!(Some(10.0), styles.body.size);
+    }
+
+    #[test]
+    fn test_marshal_filter() {
+        let rule = Rule::marshal(&read_to_string("test_case/rule/filter.yml").unwrap()).unwrap();
+        let block = &rule.doc.blocks[0];
+        assert!(block.autofilter);
+        assert!(!block.columns[2].dropdown);
+        let status = &block.columns[3];
+        assert!(status.dropdown);
+        assert_eq!(2, status.value_styles.len());
+        assert_eq!(Some(String::from("Fail")), status.value_styles[0].value);
+        assert_eq!(Some(0xFFEB9C), status.value_styles[1].style.background);
+    }
+
+    #[test]
+    fn test_marshal_cover() {
+        let rule = Rule::marshal(&read_to_string("test_case/rule/cover.yml").unwrap()).unwrap();
+        assert_eq!(Some(String::from("Login Test Report")), rule.general.title);
+        assert_eq!(Some(String::from("1.2")), rule.general.version);
+        assert_eq!(Some(String::from("2021-04-01")), rule.general.date);
+        let expected = Cover {
+            name: String::from("Summary"),
+            count_by: Some(String::from("Tester")),
+        };
+        assert_eq!(Some(expected), rule.cover);
+        let rule = Rule::marshal("cover: true\ndoc:\n  blocks: []").unwrap();
+        assert_eq!(Some(Cover::default()), rule.cover);
+        let rule = Rule::marshal("cover: false\ndoc:\n  blocks: []").unwrap();
+        assert_eq!(None, rule.cover);
+        assert!(Rule::marshal("cover: 1\ndoc:\n  blocks: []").is_err());
+    }
+
+    #[test]
+    fn test_marshal_layout() {
+        let rule = Rule::marshal(&read_to_string("test_case/rule/layout.yml").unwrap()).unwrap();
+        let expected = Layout {
+            origin: (4, 2),
+            gap: 2,
+            direction: Direction::Horizontal,
+            auto_fit: true,
+            freeze: true,
+        };
+        assert_eq!(expected, rule.layout);
+        let columns = &rule.doc.blocks[0].columns;
+        assert_eq!(Some(8.0), columns[0].width);
+        assert_eq!(None, columns[1].width);
+        assert_eq!(Some(40.5), columns[8].width);
+        let rule = Rule::marshal("doc:\n  blocks: []").unwrap();
+        assert_eq!(Layout::default(), rule.layout);
+        assert!(Rule::marshal("layout:\n  origin: A0\ndoc:\n  blocks: []").is_err());
+        assert!(Rule::marshal("layout:\n  freeze: 1\ndoc:\n  blocks: []").is_err());
+    }
+
+    #[test]
+    fn test_format_id() {
+        let general = General::default();
+        assert_eq!(String::from("1"), general.format_id(1));
+        let general = General {
+            prefix: Some(String::from("IT")),
+            ..Default::default()
+        };
+        assert_eq!(String::from("IT-001"), general.format_id(1));
+        assert_eq!(String::from("IT-1000"), general.format_id(1000));
+    }
+
+    #[test]
+    fn test_marshal_various_list_multi_char_prefix() {
+        let rule = Rule::marshal(
+            &read_to_string("test_case/rule/various_list_multi_char_prefix.yml").unwrap(),
         )
-        .is_err());
+        .unwrap();
+        assert_eq!(
+            Some(String::from("++")),
+            rule.doc.blocks[0].columns[9].custom_prefix
+        );
+    }
+
+    #[test]
+    fn test_marshal_prefix_with_whitespace() {
+        for prefix in ["\"\"", "\"Expected Result:\""].iter() {
+            let input = format!(
+                "doc:\n  blocks:\n    - title: Block Title\n      content:\n      - column: Procedure\n        md: List\n        customPrefix: {}",
+                prefix
+            );
+            assert!(Rule::marshal(&input).is_err());
+        }
+    }
+
+    #[test]
+    fn test_filter_word_prefix() {
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/word_prefix.yml").unwrap()).unwrap();
+        let result = rule.filter(&read_to_string("test_case/input/word_prefix.md").unwrap());
+        let expected = read_to_string("test_case/input/word_prefix_filtered.md").unwrap();
+        assert_eq!(expected, result);
     }
 
     #[test]
@@ -0,0 +1,197 @@
+use std::{error::Error, fmt};
+
+use crate::utils::get_custom_prefix_as_normal_list;
+
+/// An error in the input Markdown pointing to its location.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParseError {
+    /// file name shown in the message. `<input>` is shown if not set.
+    pub file: Option<String>,
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column number counted in characters
+    pub column: usize,
+    pub message: String,
+    /// the whole line where the error is found
+    pub snippet: String,
+}
+
+impl ParseError {
+    /// Creates the error pointing to the first non-whitespace character of the line.
+    /// `line_idx` is 0-based.
+    pub fn new(line_idx: usize, source_line: &str, message: &str) -> Self {
+        let offset = source_line.find(|c: char| !c.is_whitespace()).unwrap_or(0);
+        ParseError::at(line_idx, source_line, offset, message)
+    }
+
+    /// Creates the error pointing to the character at the byte `offset` of the line.
+    /// `line_idx` is 0-based.
+    pub fn at(line_idx: usize, source_line: &str, offset: usize, message: &str) -> Self {
+        let snippet = source_line.trim_end_matches('\r');
+        ParseError {
+            file: None,
+            line: line_idx + 1,
+            column: snippet
+                .char_indices()
+                .take_while(|(idx, _)| *idx < offset)
+                .count()
+                + 1,
+            message: message.to_string(),
+            snippet: snippet.to_string(),
+        }
+    }
+
+    pub fn with_file(self, file: &str) -> Self {
+        ParseError {
+            file: Some(file.to_string()),
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let line_number = self.line.to_string();
+        let gutter = " ".repeat(line_number.len());
+        // keep tabs so that the caret is placed under the column
+        let indent: String = self
+            .snippet
+            .chars()
+            .take(self.column - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        writeln!(f, "{}", self.message)?;
+        writeln!(
+            f,
+            "{}--> {}:{}:{}",
+            gutter,
+            self.file.as_deref().unwrap_or("<input>"),
+            self.line,
+            self.column
+        )?;
+        writeln!(f, "{} |", gutter)?;
+        writeln!(f, "{} | {}", line_number, self.snippet)?;
+        write!(f, "{} | {}^", gutter, indent)
+    }
+}
+
+impl Error for ParseError {}
+
+/// Maps the lines of the input filtered by `Rule::filter` back to the original input,
+/// since the filter rewrites custom prefix lines and may insert empty lines.
+#[derive(Debug)]
+pub struct SourceMap<'a> {
+    original: Vec<&'a str>,
+    /// original line index of each filtered line
+    lines: Vec<usize>,
+}
+
+impl<'a> SourceMap<'a> {
+    /// `first_line` is the original line index of the first line of `before_filter`.
+    pub fn new(original: &'a str, first_line: usize, before_filter: &str, filtered: &str) -> Self {
+        let before_filter: Vec<&str> = before_filter
+            .split('\n')
+            .map(|line| line.trim_end_matches('\r'))
+            .collect();
+        let rewritten_prefix = get_custom_prefix_as_normal_list("");
+        let mut lines = vec![];
+        let mut idx = 0;
+        for line in filtered.split('\n').map(|line| line.trim_end_matches('\r')) {
+            let is_same_line = before_filter.get(idx).is_some_and(|source| {
+                *source == line
+                    // the block name is removed from the separator
+                    || (line == "---" && source.starts_with("---"))
+                    || line
+                        .strip_prefix(&rewritten_prefix)
+                        .is_some_and(|rest| rest == source.trim())
+            });
+            if is_same_line {
+                lines.push(first_line + idx);
+                idx += 1;
+            } else {
+                // inserted empty line
+                lines.push(first_line + idx.saturating_sub(1));
+            }
+        }
+        SourceMap {
+            original: original.split('\n').collect(),
+            lines,
+        }
+    }
+
+    /// Creates the error at the `filtered_line`-th line (0-based) of the filtered input.
+    pub fn error(&self, filtered_line: usize, message: &str) -> ParseError {
+        self.error_at(filtered_line, "", message)
+    }
+
+    /// Creates the error at the `filtered_line`-th line (0-based) of the filtered input,
+    /// pointing to the character after `before`, which is the unescaped text before the error in the filtered line.
+    /// The error points to the first non-whitespace character if nothing is before it.
+    pub fn error_at(&self, filtered_line: usize, before: &str, message: &str) -> ParseError {
+        let line_idx = self
+            .lines
+            .get(filtered_line)
+            .copied()
+            .unwrap_or_else(|| self.original.len().saturating_sub(1));
+        let source_line = self.original.get(line_idx).copied().unwrap_or("");
+        if before.trim().is_empty() {
+            return ParseError::new(line_idx, source_line, message);
+        }
+        // the filter rewrites the custom prefix line without its indentation
+        let offset = match before.strip_prefix(&get_custom_prefix_as_normal_list("")) {
+            Some(rest) => source_line.len() - source_line.trim_start().len() + rest.len(),
+            None => before.len(),
+        };
+        ParseError::at(line_idx, source_line, offset, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let error = ParseError::new(11, "  ##### Heading", "something wrong").with_file("test.md");
+        assert_eq!(
+            "something wrong\n  --> test.md:12:3\n   |\n12 |   ##### Heading\n   |   ^",
+            error.to_string()
+        );
+        let error = ParseError::new(0, "\tabc", "something wrong");
+        assert_eq!(
+            "something wrong\n --> <input>:1:2\n  |\n1 | \tabc\n  | \t^",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn test_at() {
+        let error = ParseError::at(0, "* ## Heading", 2, "something wrong");
+        assert_eq!(3, error.column);
+        // counted in characters
+        let error = ParseError::at(0, "* あ ## Heading", 6, "something wrong");
+        assert_eq!(5, error.column);
+    }
+
+    #[test]
+    fn test_source_map() {
+        let original = "\n\n# Sheet\n## A\n+ a\n---\n## B";
+        let before_filter = "# Sheet\n## A\n+ a\n---\n## B";
+        let filtered = "# Sheet\n## A\n* !!!CUSTOMPREFIX+ a\n\n---\n## B";
+        let source_map = SourceMap::new(original, 2, before_filter, filtered);
+        assert_eq!(vec![2, 3, 4, 4, 5, 6], source_map.lines);
+        let error = source_map.error(5, "error");
+        assert_eq!(7, error.line);
+        assert_eq!("## B", error.snippet);
+        let error = source_map.error(2, "error");
+        assert_eq!(5, error.line);
+        assert_eq!("+ a", error.snippet);
+        let error = source_map.error_at(2, "* !!!CUSTOMPREFIX+ ", "error");
+        assert_eq!(3, error.column);
+
+        // the block name is removed from the separator by the filter
+        let original = "# Sheet\n--- Block\n## B";
+        let source_map = SourceMap::new(original, 0, original, "# Sheet\n\n---\n## B");
+        assert_eq!(vec![0, 0, 1, 2], source_map.lines);
+    }
+}
@@ -0,0 +1,458 @@
+use anyhow::{anyhow, Context, Result};
+use regex::Regex;
+use yaml_rust::Yaml;
+
+use crate::utils::parse_color;
+
+/// The formats of the cells in the excel output, defined by `style` in the rule.
+/// They can be defined for the whole document, each block and each column,
+/// and the lower level overrides the upper one.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Styles {
+    /// the block title
+    pub title: CellStyle,
+    /// the header rows made from the column and group titles
+    pub header: CellStyle,
+    /// the rows of the block
+    pub body: CellStyle,
+}
+
+impl Styles {
+    pub fn marshal(style: &Yaml, name: &str) -> Result<Self> {
+        // allows style key to be undefined
+        if style.is_badvalue() || style.is_null() {
+            return Ok(Styles::default());
+        }
+        if style.as_hash().is_none() {
+            return Err(anyhow!("{} must be a key-value map", name));
+        }
+        Ok(Styles {
+            title: CellStyle::marshal(&style["title"], &format!("{}.title", name))?,
+            header: CellStyle::marshal(&style["header"], &format!("{}.header", name))?,
+            body: CellStyle::marshal(&style["body"], &format!("{}.body", name))?,
+        })
+    }
+
+    /// The styles used when the rule defines nothing.
+    pub fn builtin() -> Self {
+        Styles {
+            title: CellStyle {
+                size: Some(16.0),
+                bold: Some(true),
+                ..Default::default()
+            },
+            header: CellStyle {
+                background: Some(0x00FFFF),
+                border: Some(Border::Thin),
+                align: Some(Align::CenterAcross),
+                valign: Some(VerticalAlign::Center),
+                wrap: Some(true),
+                ..Default::default()
+            },
+            body: CellStyle {
+                border: Some(Border::Thin),
+                align: Some(Align::Left),
+                valign: Some(VerticalAlign::Top),
+                wrap: Some(true),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Returns the styles overridden by `other`.
+    pub fn merge(&self, other: &Styles) -> Self {
+        Styles {
+            title: self.title.merge(&other.title),
+            header: self.header.merge(&other.header),
+            body: self.body.merge(&other.body),
+        }
+    }
+}
+
+/// The format of a cell.
+/// `None` inherits the value of the upper level.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct CellStyle {
+    pub font: Option<String>,
+    pub size: Option<f64>,
+    pub bold: Option<bool>,
+    pub italic: Option<bool>,
+    /// RGB value of the font color
+    pub color: Option<u32>,
+    /// RGB value of the fill color
+    pub background: Option<u32>,
+    pub border: Option<Border>,
+    /// RGB value of the border color
+    pub border_color: Option<u32>,
+    pub align: Option<Align>,
+    pub valign: Option<VerticalAlign>,
+    pub wrap: Option<bool>,
+}
+
+impl CellStyle {
+    fn marshal(style: &Yaml, name: &str) -> Result<Self> {
+        if style.is_badvalue() || style.is_null() {
+            return Ok(CellStyle::default());
+        }
+        if style.as_hash().is_none() {
+            return Err(anyhow!("{} must be a key-value map", name));
+        }
+        let get_str = |key: &str| -> Result<Option<&str>> {
+            match &style[key] {
+                Yaml::BadValue => Ok(None),
+                value => value
+                    .as_str()
+                    .map(Some)
+                    .with_context(|| format!("{}.{} must be string", name, key)),
+            }
+        };
+        let get_bool = |key: &str| -> Result<Option<bool>> {
+            match &style[key] {
+                Yaml::BadValue => Ok(None),
+                value => value
+                    .as_bool()
+                    .map(Some)
+                    .with_context(|| format!("{}.{} must be boolean", name, key)),
+            }
+        };
+        let get_color = |key: &str| -> Result<Option<u32>> {
+            get_str(key)?
+                .map(|color| {
+                    parse_color(color).with_context(|| {
+                        format!(
+                            "{}.{} must be a color like '#1F4E78': {:?}",
+                            name, key, color
+                        )
+                    })
+                })
+                .transpose()
+        };
+        let size = match &style["size"] {
+            Yaml::BadValue => None,
+            Yaml::Integer(size) if *size > 0 => Some(*size as f64),
+            Yaml::Real(size) => Some(
+                size.parse::<f64>()
+                    .ok()
+                    .filter(|size| *size > 0.0)
+                    .with_context(|| format!("{}.size must be positive number", name))?,
+            ),
+            _ => return Err(anyhow!("{}.size must be positive number", name)),
+        };
+        Ok(CellStyle {
+            font: get_str("font")?.map(String::from),
+            size,
+            bold: get_bool("bold")?,
+            italic: get_bool("italic")?,
+            color: get_color("color")?,
+            background: get_color("background")?,
+            border: get_str("border")?
+                .map(|border| {
+                    Border::parse(border)
+                        .with_context(|| format!("{}.border is unknown: {:?}", name, border))
+                })
+                .transpose()?,
+            border_color: get_color("borderColor")?,
+            align: get_str("align")?
+                .map(|align| {
+                    Align::parse(align)
+                        .with_context(|| format!("{}.align is unknown: {:?}", name, align))
+                })
+                .transpose()?,
+            valign: get_str("valign")?
+                .map(|valign| {
+                    VerticalAlign::parse(valign)
+                        .with_context(|| format!("{}.valign is unknown: {:?}", name, valign))
+                })
+                .transpose()?,
+            wrap: get_bool("wrap")?,
+        })
+    }
+
+    /// Returns the style overridden by `other`.
+    pub fn merge(&self, other: &CellStyle) -> Self {
+        CellStyle {
+            font: other.font.clone().or_else(|| self.font.clone()),
+            size: other.size.or(self.size),
+            bold: other.bold.or(self.bold),
+            italic: other.italic.or(self.italic),
+            color: other.color.or(self.color),
+            background: other.background.or(self.background),
+            border: other.border.or(self.border),
+            border_color: other.border_color.or(self.border_color),
+            align: other.align.or(self.align),
+            valign: other.valign.or(self.valign),
+            wrap: other.wrap.or(self.wrap),
+        }
+    }
+}
+
+/// A style applied to the body cells whose value matches in the excel and ods output.
+/// The values are matched when exporting, so the style is not a conditional format
+/// and doesn't follow the edits in the spreadsheet.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ValueStyle {
+    /// the cell equal to this value matches
+    pub value: Option<String>,
+    /// the cell whose whole text matches this regular expression matches
+    pub pattern: Option<String>,
+    pub style: CellStyle,
+}
+
+impl ValueStyle {
+    /// Parses the list such as `valueStyles: [{ value: Fail, style: { ... } }]`.
+    pub fn marshal_list(value_styles: &Yaml, name: &str) -> Result<Vec<Self>> {
+        if value_styles.is_badvalue() || value_styles.is_null() {
+            return Ok(vec![]);
+        }
+        value_styles
+            .as_vec()
+            .with_context(|| format!("{} must be array", name))?
+            .iter()
+            .map(|value_style| {
+                let value = match &value_style["value"] {
+                    Yaml::BadValue => None,
+                    Yaml::String(s) | Yaml::Real(s) => Some(s.clone()),
+                    Yaml::Integer(i) => Some(i.to_string()),
+                    Yaml::Boolean(b) => Some(b.to_string()),
+                    _ => return Err(anyhow!("{}.value must be scalar", name)),
+                };
+                let pattern = match &value_style["pattern"] {
+                    Yaml::BadValue => None,
+                    pattern => Some(
+                        pattern
+                            .as_str()
+                            .with_context(|| format!("{}.pattern must be string", name))?
+                            .to_string(),
+                    ),
+                };
+                if value.is_some() == pattern.is_some() {
+                    return Err(anyhow!(
+                        "{} must have either value or pattern: {:?}",
+                        name,
+                        value_style
+                    ));
+                }
+                Ok(ValueStyle {
+                    value,
+                    pattern,
+                    style: CellStyle::marshal(&value_style["style"], &format!("{}.style", name))?,
+                })
+            })
+            .collect()
+    }
+
+    /// Compiles `pattern` to match the whole cell.
+    pub fn compile(&self) -> Result<Option<Regex>> {
+        self.pattern
+            .as_ref()
+            .map(|pattern| {
+                Regex::new(&format!("^(?:{})$", pattern))
+                    .map_err(|e| anyhow!("invalid pattern of value style: {}", e))
+            })
+            .transpose()
+    }
+
+    /// Returns true if the cell matches the value or the pattern.
+    /// `regex` is the pattern compiled by `compile`.
+    pub fn is_match(&self, cell: &str, regex: Option<&Regex>) -> bool {
+        match (&self.value, regex) {
+            (Some(value), _) => value == cell,
+            (None, Some(regex)) => regex.is_match(cell),
+            (None, None) => false,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Border {
+    None,
+    Thin,
+    Medium,
+    Thick,
+    Dashed,
+    Dotted,
+    Double,
+    Hair,
+}
+
+impl Border {
+    fn parse(border: &str) -> Option<Self> {
+        match border {
+            "none" => Some(Border::None),
+            "thin" => Some(Border::Thin),
+            "medium" => Some(Border::Medium),
+            "thick" => Some(Border::Thick),
+            "dashed" => Some(Border::Dashed),
+            "dotted" => Some(Border::Dotted),
+            "double" => Some(Border::Double),
+            "hair" => Some(Border::Hair),
+            _ => None,
+        }
+    }
+}
+
+/// Horizontal alignment.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+    Justify,
+    /// centers the text across the selected cells
+    CenterAcross,
+}
+
+impl Align {
+    fn parse(align: &str) -> Option<Self> {
+        match align {
+            "left" => Some(Align::Left),
+            "center" => Some(Align::Center),
+            "right" => Some(Align::Right),
+            "justify" => Some(Align::Justify),
+            "centerAcross" => Some(Align::CenterAcross),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum VerticalAlign {
+    Top,
+    Center,
+    Bottom,
+}
+
+impl VerticalAlign {
+    fn parse(valign: &str) -> Option<Self> {
+        match valign {
+            "top" => Some(VerticalAlign::Top),
+            "center" => Some(VerticalAlign::Center),
+            "bottom" => Some(VerticalAlign::Bottom),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yaml_rust::YamlLoader;
+
+    use super::*;
+
+    fn load(input: &str) -> Yaml {
+        YamlLoader::load_from_str(input).unwrap().remove(0)
+    }
+
+    #[test]
+    fn test_marshal() {
+        let styles = Styles::marshal(
+            &load("title:\n  font: Meiryo\n  size: 14\nheader:\n  background: \"#1F4E78\"\n  color: \"#FFFFFF\"\n  border: medium\nbody:\n  size: 10.5\n  align: center\n  valign: bottom\n  wrap: false"),
+            "style",
+        )
+        .unwrap();
+        let expected = Styles {
+            title: CellStyle {
+                font: Some(String::from("Meiryo")),
+                size: Some(14.0),
+                ..Default::default()
+            },
+            header: CellStyle {
+                color: Some(0xFFFFFF),
+                background: Some(0x1F4E78),
+                border: Some(Border::Medium),
+                ..Default::default()
+            },
+            body: CellStyle {
+                size: Some(10.5),
+                align: Some(Align::Center),
+                valign: Some(VerticalAlign::Bottom),
+                wrap: Some(false),
+                ..Default::default()
+            },
+        };
+        assert_eq!(expected, styles);
+        assert_eq!(
+            Styles::default(),
+            Styles::marshal(&Yaml::BadValue, "style").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_marshal_malformed() {
+        for input in [
+            "title: bold",
+            "title:\n  size: 0",
+            "header:\n  color: blue",
+            "body:\n  border: wavy",
+            "body:\n  wrap: yes please",
+        ]
+        .iter()
+        {
+            assert!(Styles::marshal(&load(input), "style").is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_marshal_value_styles() {
+        let value_styles = ValueStyle::marshal_list(
+            &load("- value: Fail\n  style:\n    color: \"#9C0006\"\n- pattern: \"BUG-[0-9]+\"\n  style:\n    italic: true"),
+            "valueStyles",
+        )
+        .unwrap();
+        let expected = vec![
+            ValueStyle {
+                value: Some(String::from("Fail")),
+                pattern: None,
+                style: CellStyle {
+                    color: Some(0x9C0006),
+                    ..Default::default()
+                },
+            },
+            ValueStyle {
+                value: None,
+                pattern: Some(String::from("BUG-[0-9]+")),
+                style: CellStyle {
+                    italic: Some(true),
+                    ..Default::default()
+                },
+            },
+        ];
+        assert_eq!(expected, value_styles);
+        assert!(
+            ValueStyle::marshal_list(&load("- style:\n    bold: true"), "valueStyles").is_err()
+        );
+        assert!(value_styles[0].is_match("Fail", None));
+        assert!(!value_styles[0].is_match("Failed", None));
+        let regex = value_styles[1].compile().unwrap();
+        assert!(value_styles[1].is_match("BUG-12", regex.as_ref()));
+        assert!(!value_styles[1].is_match("see BUG-12", regex.as_ref()));
+        assert!(ValueStyle::marshal_list(&load("value: Fail"), "valueStyles").is_err());
+    }
+
+    #[test]
+    fn test_merge() {
+        let document = Styles {
+            body: CellStyle {
+                font: Some(String::from("Meiryo")),
+                size: Some(10.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let column = Styles {
+            body: CellStyle {
+                size: Some(9.0),
+                align: Some(Align::Right),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let merged = Styles::builtin().merge(&document).merge(&column);
+        assert_eq!(Some(String::from("Meiryo")), merged.body.font);
+        assert_eq!(Some(9.0), merged.body.size);
+        assert_eq!(Some(Align::Right), merged.body.align);
+        // inherited from the builtin styles
+        assert_eq!(Some(Border::Thin), merged.body.border);
+        assert_eq!(Styles::builtin().title, merged.title);
+    }
+}
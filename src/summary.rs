@@ -0,0 +1,135 @@
+use std::collections::BTreeMap;
+
+use crate::{data::Sheet, rule::Rule};
+
+/// The statistics of a sheet listed in the cover sheet.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SheetSummary {
+    /// name of the worksheet
+    pub name: String,
+    /// titles of the blocks in the sheet
+    pub blocks: Vec<String>,
+    /// number of the rows in all blocks
+    pub rows: usize,
+    /// number of the rows for each value of the `countBy` column
+    pub counts: BTreeMap<String, usize>,
+}
+
+/// Returns the name of the worksheet, which is `Sheet1`, `Sheet2`, ... if the sheet has no name.
+pub fn sheet_name(sheet: &Sheet, idx: usize) -> String {
+    sheet
+        .name()
+        .map(|name| name.to_string())
+        .unwrap_or_else(|| format!("Sheet{}", idx + 1))
+}
+
+/// Summarizes every sheet.
+/// Rows whose `countBy` column is empty are not counted in `counts`.
+pub fn summarize(sheets: &[Sheet], rule: &Rule) -> Vec<SheetSummary> {
+    let count_by = rule
+        .cover
+        .as_ref()
+        .and_then(|cover| cover.count_by.as_ref());
+    sheets
+        .iter()
+        .enumerate()
+        .map(|(idx, sheet)| {
+            let mut rows = 0;
+            let mut counts = BTreeMap::new();
+            for block in sheet.blocks().iter() {
+                rows += block.rows().len();
+                let (count_by, b) = match (count_by, rule.doc.blocks.get(block.rule_idx())) {
+                    (Some(count_by), Some(b)) => (count_by, b),
+                    _ => continue,
+                };
+                for row in block.rows().iter() {
+                    match row.get(&b.columns, count_by).map(|cell| cell.trim()) {
+                        Some(value) if !value.is_empty() => {
+                            *counts.entry(value.to_string()).or_insert(0) += 1
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            SheetSummary {
+                name: sheet_name(sheet, idx),
+                blocks: sheet
+                    .blocks()
+                    .iter()
+                    .map(|block| block.title().to_string())
+                    .collect(),
+                rows,
+                counts,
+            }
+        })
+        .collect()
+}
+
+/// Returns every value counted in the summaries in order.
+pub fn count_keys(summaries: &[SheetSummary]) -> Vec<&str> {
+    let mut keys: Vec<&str> = summaries
+        .iter()
+        .flat_map(|summary| summary.counts.keys().map(|key| key.as_str()))
+        .collect();
+    keys.sort_unstable();
+    keys.dedup();
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use super::*;
+
+    use crate::data::Data;
+
+    #[test]
+    fn test_summarize() {
+        let rule = Rule::marshal(&read_to_string("test_case/rule/cover.yml").unwrap()).unwrap();
+        let data = Data::marshal(
+            &read_to_string("test_case/input/cover.md").unwrap(),
+            rule.clone(),
+        )
+        .unwrap();
+        let summaries = summarize(data.sheets(), &rule);
+        let expected = vec![
+            SheetSummary {
+                name: String::from("Login"),
+                blocks: vec![String::from("Test Cases")],
+                rows: 3,
+                counts: vec![(String::from("Alice"), 2), (String::from("Bob"), 1)]
+                    .into_iter()
+                    .collect(),
+            },
+            SheetSummary {
+                name: String::from("Logout"),
+                blocks: vec![String::from("Test Cases")],
+                rows: 2,
+                counts: vec![(String::from("Carol"), 1)].into_iter().collect(),
+            },
+        ];
+        assert_eq!(expected, summaries);
+        assert_eq!(vec!["Alice", "Bob", "Carol"], count_keys(&summaries));
+    }
+
+    #[test]
+    fn test_summarize_without_count_by() {
+        let rule =
+            Rule::marshal(&read_to_string("test_case/rule/default_rule.yml").unwrap()).unwrap();
+        let data = Data::marshal(
+            &read_to_string("test_case/input/single_block_multi_row.md").unwrap(),
+            rule.clone(),
+        )
+        .unwrap();
+        let summaries = summarize(data.sheets(), &rule);
+        assert_eq!(1, summaries.len());
+        assert!(summaries[0].counts.is_empty());
+        assert!(count_keys(&summaries).is_empty());
+    }
+
+    #[test]
+    fn test_sheet_name() {
+        assert_eq!("Sheet2", sheet_name(&Sheet::default(), 1));
+    }
+}
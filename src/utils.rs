@@ -2,12 +2,13 @@ use anyhow::{anyhow, Result};
 use log::info;
 use pulldown_cmark::Tag;
 
-use crate::constant::CUSTOM_PREFIX_KEY;
+use crate::constant::{CUSTOM_PREFIX_KEY, OUTPUT_EXTENSIONS};
 
 pub fn cmarktag_stringify(tag: &Tag<'_>) -> Option<String> {
     match tag {
         Tag::Heading(idx) => Some(format!("Heading{}", idx)),
-        Tag::List(_) => Some("List".to_string()),
+        Tag::List(Some(_)) => Some("OrderedList".to_string()),
+        Tag::List(None) => Some("UnorderedList".to_string()),
         _ => None,
     }
 }
@@ -20,35 +21,237 @@ pub fn get_custom_prefix_as_normal_list(prefix: &str) -> String {
     format!("* !!!{}{}", CUSTOM_PREFIX_KEY.clone(), prefix)
 }
 
-pub fn custom_prefix_to_key(text_with_custom_prefix: Option<&str>) -> Option<String> {
-    if let Some(text) = text_with_custom_prefix {
-        if let Some(stripped) = text.strip_prefix(&format!("!!!{}", CUSTOM_PREFIX_KEY.clone())) {
-            if let Some(prefix) = stripped.chars().next() {
-                return Some(get_custom_prefix_key(&prefix.to_string()));
-            }
-        }
+/// Splits `!!!CUSTOMPREFIX<prefix> <text>` into the prefix and the text.
+/// Since custom prefixes never contain whitespaces, the prefix ends at the first space.
+fn split_custom_prefix(text_with_custom_prefix: &str) -> Option<(&str, &str)> {
+    let stripped =
+        text_with_custom_prefix.strip_prefix(&format!("!!!{}", CUSTOM_PREFIX_KEY.clone()))?;
+    let prefix_end = stripped.find(' ').unwrap_or(stripped.len());
+    if prefix_end == 0 {
+        return None;
     }
-    None
+    let (prefix, text) = stripped.split_at(prefix_end);
+    Some((prefix, text.strip_prefix(' ').unwrap_or(text)))
 }
 
-pub fn get_custom_prefix_end_idx() -> usize {
-    format!("!!!{}{}", CUSTOM_PREFIX_KEY.clone(), "a").len() + 1
+pub fn custom_prefix_to_key(text_with_custom_prefix: Option<&str>) -> Option<String> {
+    let (prefix, _) = split_custom_prefix(text_with_custom_prefix?)?;
+    Some(get_custom_prefix_key(prefix))
+}
+
+/// Returns the byte index where the text starts in `!!!CUSTOMPREFIX<prefix> <text>`.
+pub fn get_custom_prefix_end_idx(text_with_custom_prefix: &str) -> usize {
+    match split_custom_prefix(text_with_custom_prefix) {
+        Some((_, text)) => text_with_custom_prefix.len() - text.len(),
+        None => 0,
+    }
 }
 
 pub fn get_output_filename(filename: &str) -> Result<&str> {
     if filename.is_empty() {
         Err(anyhow!("output filename is empty."))
     } else {
-        let result = if let Some(stripped) = filename.strip_suffix(".xlsx") {
-            stripped
-        } else {
-            filename
-        };
+        let result = OUTPUT_EXTENSIONS
+            .iter()
+            .find_map(|ext| filename.strip_suffix(ext))
+            .unwrap_or(filename);
         info!("output filename without extension: {}", result);
         Ok(result)
     }
 }
 
+/// Replaces characters which can not be used in file names with `_`.
+pub fn sanitize_filename(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_whitespace() || c.is_control() => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// Returns true if the line opens or closes a fenced code block.
+/// Sheet names and separators in code blocks are a part of the code.
+pub fn is_code_fence(line: &str) -> bool {
+    line.trim_start().starts_with("```")
+}
+
+/// Returns the block name in the block separator `--- <name>`.
+/// `None` means the line is not a separator or the separator has no name.
+pub fn get_separator_block_name(line: &str) -> Option<&str> {
+    let name = line
+        .trim_end()
+        .strip_prefix("---")?
+        .trim_start_matches('-')
+        .trim();
+    Some(name).filter(|name| !name.is_empty())
+}
+
+/// Returns the block name in the HTML comment `<!-- block: <name> -->`.
+pub fn get_comment_block_name(line: &str) -> Option<&str> {
+    let name = line
+        .trim()
+        .strip_prefix("<!--")?
+        .strip_suffix("-->")?
+        .trim()
+        .strip_prefix("block:")?
+        .trim();
+    Some(name).filter(|name| !name.is_empty())
+}
+
+/// Parses the cell reference in A1 notation (e.g. `B5`) into the 0-based row and column.
+pub fn parse_cell_ref(cell: &str) -> Option<(u32, u16)> {
+    let cell = cell.trim();
+    let split = cell.find(|c: char| !c.is_ascii_alphabetic())?;
+    let (letters, digits) = cell.split_at(split);
+    if letters.is_empty() || letters.len() > 3 || !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let col = letters.chars().fold(0u32, |col, c| {
+        col * 26 + (c.to_ascii_uppercase() as u32 - 'A' as u32 + 1)
+    });
+    let row: u32 = digits.parse().ok()?;
+    // the limits of xlsx
+    if col > 16_384 || row == 0 || row > 1_048_576 {
+        return None;
+    }
+    Some((row - 1, (col - 1) as u16))
+}
+
+/// Parses the color in `#RRGGBB` notation into its RGB value.
+pub fn parse_color(color: &str) -> Option<u32> {
+    let hex = color.trim().strip_prefix('#')?;
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+}
+
+/// Returns the width of the text on display, where wide characters such as CJK take two columns.
+/// This is a rough estimation since the actual width depends on the font.
+pub fn display_width(text: &str) -> usize {
+    text.chars()
+        .map(|c| if (c as u32) < 0x1100 { 1 } else { 2 })
+        .sum()
+}
+
+/// Estimates the column width in characters from the longest line of the cell text.
+pub fn estimate_column_width(text: &str) -> f64 {
+    let longest = text.lines().map(display_width).max().unwrap_or(0);
+    // padding for the borders, and the maximum width of a column in excel
+    (longest as f64 + 2.0).min(255.0)
+}
+
+/// Estimates the row height in points from the line count of the cell text.
+/// `font_size` defaults to 11pt, whose default row height is 15pt.
+pub fn estimate_row_height(text: &str, font_size: Option<f64>) -> f64 {
+    let lines = text.lines().count().max(1);
+    lines as f64 * font_size.unwrap_or(11.0) * 15.0 / 11.0
+}
+
+/// A range of the columns, e.g. the columns in a group.
+#[derive(Debug)]
+pub struct CellRange {
+    from: u16,
+    to: u16,
+}
+
+impl CellRange {
+    pub fn new(from: u16, to: u16) -> Self {
+        CellRange { from, to }
+    }
+
+    pub fn contain(&self, pos: u16) -> bool {
+        self.from <= pos && pos <= self.to
+    }
+}
+
+/// Converts the fields into a CSV record (RFC 4180).
+/// Fields which contain `,`, `"` or line breaks are quoted.
+pub fn to_csv_record(fields: &[String]) -> String {
+    fields
+        .iter()
+        .map(|field| {
+            if field.contains(&[',', '"', '\n', '\r'][..]) {
+                format!("\"{}\"", field.replace("\"", "\"\""))
+            } else {
+                field.to_string()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+/// Escapes the characters which have special meanings in HTML.
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Checks if the link target is safe to be written as `href`,
+/// which is `http:`, `https:`, `mailto:` or a relative one without the scheme.
+/// Whitespaces and control characters are ignored like the browsers do, e.g. `java\tscript:`.
+pub fn is_safe_link(link: &str) -> bool {
+    let link: String = link
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect();
+    match link.find(':') {
+        // `:` after the path, query or fragment started is not a scheme
+        Some(idx) if !link[..idx].contains(&['/', '?', '#'][..]) => ["http", "https", "mailto"]
+            .iter()
+            .any(|scheme| link[..idx].eq_ignore_ascii_case(scheme)),
+        _ => true,
+    }
+}
+
+/// Escapes the Markdown punctuation in the text, so it's not taken as the inline formatting.
+pub fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '~' | '&') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Converts the link target into the `<...>` destination of a Markdown link,
+/// which can have spaces and parentheses in it.
+/// `|` is also escaped, since it ends the cell of a table.
+pub fn to_link_destination(link: &str) -> String {
+    let mut escaped = String::with_capacity(link.len() + 2);
+    escaped.push('<');
+    for c in link.chars() {
+        if matches!(c, '\\' | '<' | '>' | '|') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped.push('>');
+    escaped
+}
+
+/// Escapes the text in a cell of a GitHub-flavored Markdown table.
+/// `|` ends the cell, and `<` and `>` may be taken as HTML tags.
+pub fn escape_table_cell(text: &str) -> String {
+    text.replace("|", "\\|")
+        .replace("<", "&lt;")
+        .replace(">", "&gt;")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -59,6 +262,175 @@ mod tests {
         assert_eq!("output", get_output_filename("output.xlsx").unwrap());
     }
 
+    #[test]
+    fn test_get_output_filename_other_formats() {
+        assert_eq!("output", get_output_filename("output.csv").unwrap());
+        assert_eq!("output", get_output_filename("output.json").unwrap());
+        assert_eq!("output", get_output_filename("output.html").unwrap());
+        assert_eq!("output", get_output_filename("output.ods").unwrap());
+        assert_eq!("output", get_output_filename("output.md").unwrap());
+    }
+
+    #[test]
+    fn test_cell_range_contain() {
+        let cell_range = CellRange::new(0, 10);
+        assert!(cell_range.contain(0));
+        assert!(cell_range.contain(10));
+        assert!(cell_range.contain(5));
+        assert!(!cell_range.contain(11));
+    }
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!("plain", escape_html("plain"));
+        assert_eq!(
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&#39;s&lt;/a&gt;",
+            escape_html("<a href=\"x\">Tom & Jerry's</a>")
+        );
+    }
+
+    #[test]
+    fn test_is_safe_link() {
+        assert!(is_safe_link("https://example.com"));
+        assert!(is_safe_link("HTTP://example.com"));
+        assert!(is_safe_link("mailto:user@example.com"));
+        assert!(is_safe_link("docs/index.html"));
+        assert!(is_safe_link("#sheet-1"));
+        assert!(is_safe_link("search?q=a:b"));
+        assert!(!is_safe_link("javascript:alert(1)"));
+        assert!(!is_safe_link(" JavaScript:alert(1)"));
+        assert!(!is_safe_link("java\tscript:alert(1)"));
+        assert!(!is_safe_link("data:text/html,<script>alert(1)</script>"));
+    }
+
+    #[test]
+    fn test_escape_markdown() {
+        assert_eq!("plain", escape_markdown("plain"));
+        assert_eq!(
+            "2 \\* 3, \\_id\\_, \\`x\\`, \\[a\\](b), \\~\\~s\\~\\~, \\&lt; and C:\\\\",
+            escape_markdown("2 * 3, _id_, `x`, [a](b), ~~s~~, &lt; and C:\\")
+        );
+    }
+
+    #[test]
+    fn test_to_link_destination() {
+        assert_eq!(
+            "<https://example.com>",
+            to_link_destination("https://example.com")
+        );
+        assert_eq!(
+            "<a b(c)\\|d\\>\\<\\\\>",
+            to_link_destination("a b(c)|d><\\")
+        );
+    }
+
+    #[test]
+    fn test_escape_table_cell() {
+        assert_eq!("plain", escape_table_cell("plain"));
+        assert_eq!("a \\| &lt;b&gt;", escape_table_cell("a | <b>"));
+    }
+
+    #[test]
+    fn test_sanitize_filename() {
+        assert_eq!("Sheet_Name_1", sanitize_filename("Sheet Name 1"));
+        assert_eq!("a_b_c", sanitize_filename("a/b\\c"));
+    }
+
+    #[test]
+    fn test_to_csv_record() {
+        let fields = vec![
+            String::from("1"),
+            String::from("plain"),
+            String::from("with,comma"),
+            String::from("with \"quote\""),
+            String::from("multi\nline"),
+            String::default(),
+        ];
+        assert_eq!(
+            "1,plain,\"with,comma\",\"with \"\"quote\"\"\",\"multi\nline\",",
+            to_csv_record(&fields)
+        );
+    }
+
+    #[test]
+    fn test_parse_cell_ref() {
+        assert_eq!(Some((0, 0)), parse_cell_ref("A1"));
+        assert_eq!(Some((4, 1)), parse_cell_ref("B5"));
+        assert_eq!(Some((9, 27)), parse_cell_ref("ab10"));
+        assert_eq!(Some((1_048_575, 16_383)), parse_cell_ref("XFD1048576"));
+        assert_eq!(None, parse_cell_ref("XFE1"));
+        assert_eq!(None, parse_cell_ref("A0"));
+        assert_eq!(None, parse_cell_ref("5B"));
+        assert_eq!(None, parse_cell_ref("B"));
+        assert_eq!(None, parse_cell_ref("B5C"));
+    }
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(Some(0x1F4E78), parse_color("#1F4E78"));
+        assert_eq!(Some(0xFFFFFF), parse_color("#ffffff"));
+        assert_eq!(None, parse_color("1F4E78"));
+        assert_eq!(None, parse_color("#FFF"));
+        assert_eq!(None, parse_color("#GGGGGG"));
+    }
+
+    #[test]
+    fn test_estimate_size() {
+        assert_eq!(2, display_width("ab"));
+        assert_eq!(6, display_width("テスト"));
+        assert_eq!(13.0, estimate_column_width("short\nlonger line"));
+        assert_eq!(8.0, estimate_column_width("テスト"));
+        assert_eq!(2.0, estimate_column_width(""));
+        assert_eq!(255.0, estimate_column_width(&"a".repeat(300)));
+        assert_eq!(45.0, estimate_row_height("1\n2\n3", None));
+        assert_eq!(15.0, estimate_row_height("", None));
+        assert_eq!(30.0, estimate_row_height("1\n2", Some(11.0)));
+    }
+
+    #[test]
+    fn test_block_name() {
+        assert_eq!(
+            Some("Block Title 2"),
+            get_separator_block_name("--- Block Title 2 ")
+        );
+        assert_eq!(Some("cases"), get_separator_block_name("-----cases"));
+        assert_eq!(None, get_separator_block_name("---"));
+        assert_eq!(None, get_separator_block_name("## --- title"));
+        assert_eq!(
+            Some("Block Title 2"),
+            get_comment_block_name("<!-- block: Block Title 2 -->")
+        );
+        assert_eq!(Some("cases"), get_comment_block_name("<!--block:cases-->"));
+        assert_eq!(None, get_comment_block_name("<!-- block: -->"));
+        assert_eq!(None, get_comment_block_name("<!-- note -->"));
+    }
+
+    #[test]
+    fn test_custom_prefix_to_key() {
+        for prefix in ["+", "Expected:", "→"].iter() {
+            let text = format!(
+                "{} text",
+                get_custom_prefix_as_normal_list(prefix)
+                    .strip_prefix("* ")
+                    .unwrap()
+            );
+            assert_eq!(
+                Some(get_custom_prefix_key(prefix)),
+                custom_prefix_to_key(Some(&text))
+            );
+            assert_eq!("text", &text[get_custom_prefix_end_idx(&text)..]);
+        }
+        // the text can be empty
+        let text = format!("!!!{}Pre:", CUSTOM_PREFIX_KEY.clone());
+        assert_eq!(
+            Some(get_custom_prefix_key("Pre:")),
+            custom_prefix_to_key(Some(&text))
+        );
+        assert_eq!("", &text[get_custom_prefix_end_idx(&text)..]);
+        assert!(custom_prefix_to_key(Some("normal text")).is_none());
+        assert!(custom_prefix_to_key(None).is_none());
+    }
+
     #[test]
     fn test_get_output_filename_error() {
         assert!(get_output_filename("").is_err());
@@ -0,0 +1,1259 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use anyhow::Result;
+use regex::Regex;
+use yaml_rust::{
+    parser::{Event, MarkedEventReceiver, Parser},
+    scanner::{Marker, TScalarStyle},
+    Yaml,
+};
+
+use crate::{
+    rule::scalar_to_string,
+    utils::{parse_cell_ref, parse_color},
+};
+
+const ROOT_KEYS: &[&str] = &["general", "layout", "style", "cover", "doc"];
+const GENERAL_KEYS: &[&str] = &[
+    "copyright",
+    "prefix",
+    "unmapped",
+    "highlight",
+    "title",
+    "version",
+    "date",
+];
+const GENERAL_STRING_KEYS: &[&str] = &["copyright", "prefix", "unmapped", "title", "date"];
+const LAYOUT_KEYS: &[&str] = &["origin", "gap", "direction", "autoFit", "freeze"];
+const DIRECTIONS: &[&str] = &["vertical", "horizontal"];
+const DOC_KEYS: &[&str] = &["blocks"];
+const COVER_KEYS: &[&str] = &["name", "countBy"];
+const STYLE_KEYS: &[&str] = &["title", "header", "body"];
+const COLUMN_STYLE_KEYS: &[&str] = &["header", "body"];
+const CELL_STYLE_KEYS: &[&str] = &[
+    "font",
+    "size",
+    "bold",
+    "italic",
+    "color",
+    "background",
+    "border",
+    "borderColor",
+    "align",
+    "valign",
+    "wrap",
+];
+const BORDERS: &[&str] = &[
+    "none", "thin", "medium", "thick", "dashed", "dotted", "double", "hair",
+];
+const ALIGNS: &[&str] = &["left", "center", "right", "justify", "centerAcross"];
+const VERTICAL_ALIGNS: &[&str] = &["top", "center", "bottom"];
+const BLOCK_KEYS: &[&str] = &["title", "id", "style", "autofilter", "content"];
+const GROUP_KEYS: &[&str] = &["group", "columns"];
+const COLUMN_KEYS: &[&str] = &[
+    "column",
+    "isNum",
+    "md",
+    "customPrefix",
+    "overflow",
+    "required",
+    "maxLength",
+    "pattern",
+    "enum",
+    "dropdown",
+    "valueStyles",
+    "width",
+    "style",
+];
+const VALUE_STYLE_KEYS: &[&str] = &["value", "pattern", "style"];
+const MD_TAGS: &[&str] = &[
+    "Heading2",
+    "Heading3",
+    "Heading4",
+    "Heading5",
+    "Heading6",
+    "Heading7",
+    "Heading8",
+    "List",
+    "OrderedList",
+    "UnorderedList",
+];
+const UNMAPPED_POLICIES: &[&str] = &["error", "warning", "overflow"];
+
+/// A problem found in the rule file.
+/// `line` and `column` are 1-based positions of the offending node.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(marker: &Marker, message: String) -> Self {
+        Diagnostic {
+            line: marker.line(),
+            column: marker.col() + 1,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+/// Validates the rule file against its schema and returns every problem found.
+/// Unlike `Rule::marshal`, this keeps the position of each node,
+/// so that the problems can be reported with the line and column.
+pub fn validate(input: &str) -> Result<Vec<Diagnostic>> {
+    let mut builder = NodeBuilder::default();
+    let mut parser = Parser::new(input.chars());
+    parser.load(&mut builder, false)?;
+    let mut diagnostics = vec![];
+    match &builder.root {
+        Some(root) => validate_root(root, &mut diagnostics),
+        None => diagnostics.push(Diagnostic {
+            line: 1,
+            column: 1,
+            message: String::from("rule file is empty"),
+        }),
+    }
+    Ok(diagnostics)
+}
+
+#[derive(Debug)]
+enum NodeKind {
+    Scalar(Yaml),
+    Sequence(Vec<Node>),
+    Mapping(Vec<(Node, Node)>),
+    Alias,
+}
+
+#[derive(Debug)]
+struct Node {
+    kind: NodeKind,
+    marker: Marker,
+}
+
+impl Node {
+    fn as_str(&self) -> Option<&str> {
+        if let NodeKind::Scalar(Yaml::String(s)) = &self.kind {
+            Some(s)
+        } else {
+            None
+        }
+    }
+
+    fn type_name(&self) -> &'static str {
+        match &self.kind {
+            NodeKind::Scalar(Yaml::String(_)) => "string",
+            NodeKind::Scalar(Yaml::Boolean(_)) => "boolean",
+            NodeKind::Scalar(Yaml::Integer(_)) | NodeKind::Scalar(Yaml::Real(_)) => "number",
+            NodeKind::Scalar(Yaml::Null) => "null",
+            NodeKind::Scalar(_) => "scalar",
+            NodeKind::Sequence(_) => "array",
+            NodeKind::Mapping(_) => "map",
+            NodeKind::Alias => "alias",
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<&Node> {
+        if let NodeKind::Mapping(entries) = &self.kind {
+            return entries
+                .iter()
+                .find(|(k, _)| k.as_str() == Some(key))
+                .map(|(_, v)| v);
+        }
+        None
+    }
+}
+
+/// Builds a tree of `Node` from the parser events.
+#[derive(Default)]
+struct NodeBuilder {
+    root: Option<Node>,
+    // containers under construction and their pending keys
+    stack: Vec<(Node, Option<Node>)>,
+}
+
+impl NodeBuilder {
+    fn insert(&mut self, node: Node) {
+        match self.stack.last_mut() {
+            None => {
+                // only the first document is used
+                if self.root.is_none() {
+                    self.root = Some(node);
+                }
+            }
+            Some((parent, pending_key)) => match &mut parent.kind {
+                NodeKind::Sequence(items) => items.push(node),
+                NodeKind::Mapping(entries) => {
+                    if let Some(key) = pending_key.take() {
+                        entries.push((key, node));
+                    } else {
+                        *pending_key = Some(node);
+                    }
+                }
+                _ => unreachable!(),
+            },
+        }
+    }
+}
+
+impl MarkedEventReceiver for NodeBuilder {
+    fn on_event(&mut self, ev: Event, marker: Marker) {
+        match ev {
+            Event::Scalar(value, style, _, _) => {
+                let yaml = if style == TScalarStyle::Plain {
+                    Yaml::from_str(&value)
+                } else {
+                    Yaml::String(value)
+                };
+                self.insert(Node {
+                    kind: NodeKind::Scalar(yaml),
+                    marker,
+                });
+            }
+            Event::Alias(_) => self.insert(Node {
+                kind: NodeKind::Alias,
+                marker,
+            }),
+            Event::SequenceStart(_) => self.stack.push((
+                Node {
+                    kind: NodeKind::Sequence(vec![]),
+                    marker,
+                },
+                None,
+            )),
+            Event::MappingStart(_) => self.stack.push((
+                Node {
+                    kind: NodeKind::Mapping(vec![]),
+                    marker,
+                },
+                None,
+            )),
+            Event::SequenceEnd | Event::MappingEnd => {
+                if let Some((mut node, _)) = self.stack.pop() {
+                    // the marker of a block mapping points to the end of its first key,
+                    // so the first key is used as the position of the mapping instead
+                    if let NodeKind::Mapping(entries) = &node.kind {
+                        if let Some((key, _)) = entries.first() {
+                            node.marker = key.marker;
+                        }
+                    }
+                    self.insert(node);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn expect_mapping<'a>(
+    node: &'a Node,
+    name: &str,
+    keys: &[&str],
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Option<&'a Vec<(Node, Node)>> {
+    if let NodeKind::Mapping(entries) = &node.kind {
+        let mut seen = HashSet::new();
+        for (key, _) in entries.iter() {
+            match key.as_str() {
+                Some(k) if !keys.contains(&k) => diagnostics.push(Diagnostic::new(
+                    &key.marker,
+                    format!(
+                        "unknown key '{}' in {} (expected one of: {})",
+                        k,
+                        name,
+                        keys.join(", ")
+                    ),
+                )),
+                Some(k) if !seen.insert(k) => diagnostics.push(Diagnostic::new(
+                    &key.marker,
+                    format!("duplicate key '{}' in {}", k, name),
+                )),
+                Some(_) => {}
+                None => diagnostics.push(Diagnostic::new(
+                    &key.marker,
+                    format!("keys of {} must be string", name),
+                )),
+            }
+        }
+        Some(entries)
+    } else {
+        diagnostics.push(Diagnostic::new(
+            &node.marker,
+            format!("{} must be map, but got {}", name, node.type_name()),
+        ));
+        None
+    }
+}
+
+fn expect_sequence<'a>(
+    node: &'a Node,
+    name: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Option<&'a Vec<Node>> {
+    if let NodeKind::Sequence(items) = &node.kind {
+        Some(items)
+    } else {
+        diagnostics.push(Diagnostic::new(
+            &node.marker,
+            format!("{} must be array, but got {}", name, node.type_name()),
+        ));
+        None
+    }
+}
+
+fn expect_string(node: &Node, name: &str, diagnostics: &mut Vec<Diagnostic>) {
+    if node.as_str().is_none() {
+        diagnostics.push(Diagnostic::new(
+            &node.marker,
+            format!("{} must be string, but got {}", name, node.type_name()),
+        ));
+    }
+}
+
+fn validate_root(root: &Node, diagnostics: &mut Vec<Diagnostic>) {
+    if expect_mapping(root, "rule file", ROOT_KEYS, diagnostics).is_none() {
+        return;
+    }
+    let mut overflow_required = false;
+    if let Some(general) = root.get("general") {
+        if expect_mapping(general, "general", GENERAL_KEYS, diagnostics).is_some() {
+            for key in GENERAL_STRING_KEYS.iter() {
+                if let Some(value) = general.get(key) {
+                    expect_string(value, &format!("general.{}", key), diagnostics);
+                }
+            }
+            if let Some(highlight) = general.get("highlight") {
+                if !matches!(highlight.kind, NodeKind::Scalar(Yaml::Boolean(_))) {
+                    diagnostics.push(Diagnostic::new(
+                        &highlight.marker,
+                        format!(
+                            "general.highlight must be boolean, but got {}",
+                            highlight.type_name()
+                        ),
+                    ));
+                }
+            }
+            if let Some(version) = general.get("version") {
+                if !matches!(
+                    version.kind,
+                    NodeKind::Scalar(Yaml::String(_) | Yaml::Integer(_) | Yaml::Real(_))
+                ) {
+                    diagnostics.push(Diagnostic::new(
+                        &version.marker,
+                        format!(
+                            "general.version must be string or number, but got {}",
+                            version.type_name()
+                        ),
+                    ));
+                }
+            }
+            if let Some(unmapped) = general.get("unmapped") {
+                match unmapped.as_str() {
+                    Some(policy) if !UNMAPPED_POLICIES.contains(&policy) => {
+                        diagnostics.push(Diagnostic::new(
+                            &unmapped.marker,
+                            format!(
+                                "unknown policy '{}' in general.unmapped (expected one of: {})",
+                                policy,
+                                UNMAPPED_POLICIES.join(", ")
+                            ),
+                        ))
+                    }
+                    Some(policy) => overflow_required = policy == "overflow",
+                    None => {}
+                }
+            }
+        }
+    }
+    if let Some(layout) = root.get("layout") {
+        validate_layout(layout, diagnostics);
+    }
+    if let Some(style) = root.get("style") {
+        validate_style(style, "style", STYLE_KEYS, diagnostics);
+    }
+    if let Some(cover) = root.get("cover") {
+        validate_cover(cover, &column_titles(root), diagnostics);
+    }
+    if let Some(doc) = root.get("doc") {
+        if expect_mapping(doc, "doc", DOC_KEYS, diagnostics).is_none() {
+            return;
+        }
+        if let Some(blocks) = doc.get("blocks") {
+            if let Some(blocks) = expect_sequence(blocks, "doc.blocks", diagnostics) {
+                let mut ids = HashSet::new();
+                for block in blocks.iter() {
+                    validate_block(block, overflow_required, diagnostics);
+                    // ids select the blocks by the block markers in the input
+                    if let Some(id) = block.get("id") {
+                        match id.as_str() {
+                            Some(i) if !ids.insert(i) => diagnostics.push(Diagnostic::new(
+                                &id.marker,
+                                format!("duplicate block id '{}'", i),
+                            )),
+                            Some(_) => {}
+                            None => expect_string(id, "block id", diagnostics),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn validate_layout(layout: &Node, diagnostics: &mut Vec<Diagnostic>) {
+    if expect_mapping(layout, "layout", LAYOUT_KEYS, diagnostics).is_none() {
+        return;
+    }
+    if let Some(origin) = layout.get("origin") {
+        match origin.as_str() {
+            Some(cell) if parse_cell_ref(cell).is_none() => diagnostics.push(Diagnostic::new(
+                &origin.marker,
+                format!("layout.origin must be a cell like 'A1', but got '{}'", cell),
+            )),
+            Some(_) => {}
+            None => expect_string(origin, "layout.origin", diagnostics),
+        }
+    }
+    if let Some(gap) = layout.get("gap") {
+        if !matches!(gap.kind, NodeKind::Scalar(Yaml::Integer(n)) if n >= 0) {
+            diagnostics.push(Diagnostic::new(
+                &gap.marker,
+                format!(
+                    "layout.gap must be non-negative integer, but got {}",
+                    gap.type_name()
+                ),
+            ));
+        }
+    }
+    if let Some(direction) = layout.get("direction") {
+        match direction.as_str() {
+            Some(d) if !DIRECTIONS.contains(&d) => diagnostics.push(Diagnostic::new(
+                &direction.marker,
+                format!(
+                    "unknown direction '{}' in layout.direction (expected one of: {})",
+                    d,
+                    DIRECTIONS.join(", ")
+                ),
+            )),
+            Some(_) => {}
+            None => expect_string(direction, "layout.direction", diagnostics),
+        }
+    }
+    for key in ["autoFit", "freeze"].iter() {
+        if let Some(value) = layout.get(key) {
+            if !matches!(value.kind, NodeKind::Scalar(Yaml::Boolean(_))) {
+                diagnostics.push(Diagnostic::new(
+                    &value.marker,
+                    format!(
+                        "layout.{} must be boolean, but got {}",
+                        key,
+                        value.type_name()
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+/// Collects the titles of every column in the blocks, including the ones in the groups.
+fn column_titles(root: &Node) -> Vec<&str> {
+    fn collect<'a>(content: &'a Node, titles: &mut Vec<&'a str>) {
+        if let NodeKind::Sequence(items) = &content.kind {
+            for item in items.iter() {
+                if let Some(title) = item.get("column").and_then(|c| c.as_str()) {
+                    titles.push(title);
+                }
+                if let Some(columns) = item.get("columns") {
+                    collect(columns, titles);
+                }
+            }
+        }
+    }
+    let mut titles = vec![];
+    if let Some(NodeKind::Sequence(blocks)) = root
+        .get("doc")
+        .and_then(|doc| doc.get("blocks"))
+        .map(|blocks| &blocks.kind)
+    {
+        for block in blocks.iter() {
+            if let Some(content) = block.get("content") {
+                collect(content, &mut titles);
+            }
+        }
+    }
+    titles
+}
+
+fn validate_cover(cover: &Node, columns: &[&str], diagnostics: &mut Vec<Diagnostic>) {
+    // `cover: true` uses the default settings
+    if matches!(cover.kind, NodeKind::Scalar(Yaml::Boolean(_))) {
+        return;
+    }
+    if expect_mapping(cover, "cover", COVER_KEYS, diagnostics).is_none() {
+        return;
+    }
+    if let Some(name) = cover.get("name") {
+        match name.as_str() {
+            // the restrictions of the sheet name in excel
+            Some(n)
+                if n.is_empty()
+                    || n.chars().count() > 31
+                    || n.contains(&['[', ']', ':', '*', '?', '/', '\\'][..]) =>
+            {
+                diagnostics.push(Diagnostic::new(
+                    &name.marker,
+                    format!(
+                        "cover.name must be 1 to 31 characters without []:*?/\\, but got '{}'",
+                        n
+                    ),
+                ))
+            }
+            Some(_) => {}
+            None => expect_string(name, "cover.name", diagnostics),
+        }
+    }
+    if let Some(count_by) = cover.get("countBy") {
+        match count_by.as_str() {
+            Some(c) if !columns.contains(&c) => diagnostics.push(Diagnostic::new(
+                &count_by.marker,
+                format!("cover.countBy refers to unknown column '{}'", c),
+            )),
+            Some(_) => {}
+            None => expect_string(count_by, "cover.countBy", diagnostics),
+        }
+    }
+}
+
+/// Validates the style section, which has the cell styles keyed by `keys`.
+fn validate_style(style: &Node, name: &str, keys: &[&str], diagnostics: &mut Vec<Diagnostic>) {
+    let entries = match expect_mapping(style, name, keys, diagnostics) {
+        Some(entries) => entries,
+        None => return,
+    };
+    for (key, cell_style) in entries.iter() {
+        if let Some(k) = key.as_str().filter(|k| keys.contains(k)) {
+            validate_cell_style(cell_style, &format!("{}.{}", name, k), diagnostics);
+        }
+    }
+}
+
+fn validate_cell_style(cell_style: &Node, name: &str, diagnostics: &mut Vec<Diagnostic>) {
+    if expect_mapping(cell_style, name, CELL_STYLE_KEYS, diagnostics).is_none() {
+        return;
+    }
+    if let Some(font) = cell_style.get("font") {
+        expect_string(font, &format!("{}.font", name), diagnostics);
+    }
+    if let Some(size) = cell_style.get("size") {
+        let valid = match &size.kind {
+            NodeKind::Scalar(Yaml::Integer(n)) => *n > 0,
+            NodeKind::Scalar(Yaml::Real(n)) => n.parse::<f64>().is_ok_and(|n| n > 0.0),
+            _ => false,
+        };
+        if !valid {
+            diagnostics.push(Diagnostic::new(
+                &size.marker,
+                format!("{}.size must be positive number", name),
+            ));
+        }
+    }
+    for key in ["bold", "italic", "wrap"].iter() {
+        if let Some(value) = cell_style.get(key) {
+            if !matches!(value.kind, NodeKind::Scalar(Yaml::Boolean(_))) {
+                diagnostics.push(Diagnostic::new(
+                    &value.marker,
+                    format!(
+                        "{}.{} must be boolean, but got {}",
+                        name,
+                        key,
+                        value.type_name()
+                    ),
+                ));
+            }
+        }
+    }
+    for key in ["color", "background", "borderColor"].iter() {
+        if let Some(color) = cell_style.get(key) {
+            match color.as_str() {
+                Some(c) if parse_color(c).is_none() => diagnostics.push(Diagnostic::new(
+                    &color.marker,
+                    format!(
+                        "{}.{} must be a color like '#1F4E78', but got '{}'",
+                        name, key, c
+                    ),
+                )),
+                Some(_) => {}
+                None => expect_string(color, &format!("{}.{}", name, key), diagnostics),
+            }
+        }
+    }
+    for (key, values) in [
+        ("border", BORDERS),
+        ("align", ALIGNS),
+        ("valign", VERTICAL_ALIGNS),
+    ]
+    .iter()
+    {
+        if let Some(value) = cell_style.get(key) {
+            match value.as_str() {
+                Some(v) if !values.contains(&v) => diagnostics.push(Diagnostic::new(
+                    &value.marker,
+                    format!(
+                        "unknown {} '{}' in {}.{} (expected one of: {})",
+                        key,
+                        v,
+                        name,
+                        key,
+                        values.join(", ")
+                    ),
+                )),
+                Some(_) => {}
+                None => expect_string(value, &format!("{}.{}", name, key), diagnostics),
+            }
+        }
+    }
+}
+
+fn validate_value_style(value_style: &Node, diagnostics: &mut Vec<Diagnostic>) {
+    if expect_mapping(value_style, "value style", VALUE_STYLE_KEYS, diagnostics).is_none() {
+        return;
+    }
+    match (value_style.get("value"), value_style.get("pattern")) {
+        (Some(value), None) => {
+            if !matches!(
+                value.kind,
+                NodeKind::Scalar(
+                    Yaml::String(_) | Yaml::Integer(_) | Yaml::Real(_) | Yaml::Boolean(_)
+                )
+            ) {
+                diagnostics.push(Diagnostic::new(
+                    &value.marker,
+                    format!(
+                        "value style value must be scalar, but got {}",
+                        value.type_name()
+                    ),
+                ));
+            }
+        }
+        (None, Some(pattern)) => match pattern.as_str() {
+            Some(p) if Regex::new(p).is_err() => diagnostics.push(Diagnostic::new(
+                &pattern.marker,
+                format!("pattern '{}' is not a valid regular expression", p),
+            )),
+            Some(_) => {}
+            None => expect_string(pattern, "value style pattern", diagnostics),
+        },
+        _ => diagnostics.push(Diagnostic::new(
+            &value_style.marker,
+            String::from("value style must have either value or pattern"),
+        )),
+    }
+    if let Some(style) = value_style.get("style") {
+        validate_cell_style(style, "value style", diagnostics);
+    }
+}
+
+fn validate_block(block: &Node, overflow_required: bool, diagnostics: &mut Vec<Diagnostic>) {
+    if expect_mapping(block, "block", BLOCK_KEYS, diagnostics).is_none() {
+        return;
+    }
+    if let Some(title) = block.get("title") {
+        expect_string(title, "block title", diagnostics);
+    }
+    if let Some(style) = block.get("style") {
+        validate_style(style, "block style", STYLE_KEYS, diagnostics);
+    }
+    if let Some(autofilter) = block.get("autofilter") {
+        if !matches!(autofilter.kind, NodeKind::Scalar(Yaml::Boolean(_))) {
+            diagnostics.push(Diagnostic::new(
+                &autofilter.marker,
+                format!(
+                    "autofilter must be boolean, but got {}",
+                    autofilter.type_name()
+                ),
+            ));
+        }
+    }
+    let content = match block.get("content") {
+        Some(content) => content,
+        None => return,
+    };
+    let content = match expect_sequence(content, "content", diagnostics) {
+        Some(content) => content,
+        None => return,
+    };
+    let mut columns = vec![];
+    for item in content.iter() {
+        if item.get("column").is_some() {
+            columns.push(item);
+        } else if item.get("group").is_some() {
+            if expect_mapping(item, "group", GROUP_KEYS, diagnostics).is_none() {
+                continue;
+            }
+            match item.get("columns") {
+                Some(group_columns) => {
+                    if let Some(group_columns) =
+                        expect_sequence(group_columns, "group columns", diagnostics)
+                    {
+                        columns.extend(group_columns.iter());
+                    }
+                }
+                None => diagnostics.push(Diagnostic::new(
+                    &item.marker,
+                    String::from("columns key is required in group"),
+                )),
+            }
+        } else {
+            diagnostics.push(Diagnostic::new(
+                &item.marker,
+                String::from(
+                    "all values of 'content' key must have either keys 'column' or 'group'",
+                ),
+            ));
+        }
+    }
+
+    let mut titles = HashSet::new();
+    let mut auto_increment_found = false;
+    let mut overflow_found = false;
+    // the column title for each markdown notation, used to find ambiguous mappings
+    let mut notations: HashMap<String, &str> = HashMap::new();
+    for column in columns.into_iter() {
+        if expect_mapping(column, "column", COLUMN_KEYS, diagnostics).is_none() {
+            continue;
+        }
+        match column.get("column") {
+            Some(title) => {
+                expect_string(title, "column title", diagnostics);
+                if let Some(t) = title.as_str() {
+                    if !titles.insert(t) {
+                        diagnostics.push(Diagnostic::new(
+                            &title.marker,
+                            format!("duplicate column title '{}' in the block", t),
+                        ));
+                    }
+                }
+            }
+            None => diagnostics.push(Diagnostic::new(
+                &column.marker,
+                String::from("column key is required"),
+            )),
+        }
+        if let Some(is_num) = column.get("isNum") {
+            match &is_num.kind {
+                NodeKind::Scalar(Yaml::Boolean(b)) => {
+                    if *b && auto_increment_found {
+                        diagnostics.push(Diagnostic::new(
+                            &is_num.marker,
+                            String::from("only one column can have 'isNum: true' in a block"),
+                        ));
+                    }
+                    auto_increment_found |= *b;
+                }
+                _ => diagnostics.push(Diagnostic::new(
+                    &is_num.marker,
+                    format!("isNum must be boolean, but got {}", is_num.type_name()),
+                )),
+            }
+        }
+        if let Some(overflow) = column.get("overflow") {
+            match &overflow.kind {
+                NodeKind::Scalar(Yaml::Boolean(b)) => {
+                    if *b && overflow_found {
+                        diagnostics.push(Diagnostic::new(
+                            &overflow.marker,
+                            String::from("only one column can have 'overflow: true' in a block"),
+                        ));
+                    }
+                    if *b
+                        && ["isNum", "md", "customPrefix"]
+                            .iter()
+                            .any(|key| column.get(key).is_some())
+                    {
+                        diagnostics.push(Diagnostic::new(
+                            &overflow.marker,
+                            String::from(
+                                "the column with 'overflow: true' must not have isNum, md nor customPrefix",
+                            ),
+                        ));
+                    }
+                    overflow_found |= *b;
+                }
+                _ => diagnostics.push(Diagnostic::new(
+                    &overflow.marker,
+                    format!("overflow must be boolean, but got {}", overflow.type_name()),
+                )),
+            }
+        }
+        if let Some(required) = column.get("required") {
+            if !matches!(required.kind, NodeKind::Scalar(Yaml::Boolean(_))) {
+                diagnostics.push(Diagnostic::new(
+                    &required.marker,
+                    format!("required must be boolean, but got {}", required.type_name()),
+                ));
+            }
+        }
+        if let Some(max_length) = column.get("maxLength") {
+            let got = match &max_length.kind {
+                NodeKind::Scalar(Yaml::Integer(n)) if *n > 0 => None,
+                NodeKind::Scalar(Yaml::Integer(n)) => Some(n.to_string()),
+                _ => Some(max_length.type_name().to_string()),
+            };
+            if let Some(got) = got {
+                diagnostics.push(Diagnostic::new(
+                    &max_length.marker,
+                    format!("maxLength must be positive integer, but got {}", got),
+                ));
+            }
+        }
+        if let Some(pattern) = column.get("pattern") {
+            match pattern.as_str() {
+                Some(p) if Regex::new(p).is_err() => diagnostics.push(Diagnostic::new(
+                    &pattern.marker,
+                    format!("pattern '{}' is not a valid regular expression", p),
+                )),
+                Some(_) => {}
+                None => expect_string(pattern, "pattern", diagnostics),
+            }
+        }
+        if let Some(values) = column.get("enum") {
+            match &values.kind {
+                NodeKind::Sequence(items) if items.is_empty() => diagnostics.push(Diagnostic::new(
+                    &values.marker,
+                    String::from("enum must not be empty"),
+                )),
+                NodeKind::Sequence(items) => {
+                    for item in items.iter() {
+                        if !matches!(
+                            item.kind,
+                            NodeKind::Scalar(
+                                Yaml::String(_)
+                                    | Yaml::Integer(_)
+                                    | Yaml::Real(_)
+                                    | Yaml::Boolean(_)
+                            )
+                        ) {
+                            diagnostics.push(Diagnostic::new(
+                                &item.marker,
+                                format!("enum values must be scalar, but got {}", item.type_name()),
+                            ));
+                        }
+                    }
+                }
+                _ => diagnostics.push(Diagnostic::new(
+                    &values.marker,
+                    format!("enum must be array, but got {}", values.type_name()),
+                )),
+            }
+        }
+        if let Some(dropdown) = column.get("dropdown") {
+            match &dropdown.kind {
+                NodeKind::Scalar(Yaml::Boolean(true)) => {
+                    match column.get("enum").map(|e| &e.kind) {
+                        Some(NodeKind::Sequence(items)) => {
+                            // the list of a data validation is limited to 255 characters in excel
+                            let length = items
+                                .iter()
+                                .filter_map(|item| match &item.kind {
+                                    NodeKind::Scalar(value) => scalar_to_string(value),
+                                    _ => None,
+                                })
+                                .map(|value| value.chars().count())
+                                .sum::<usize>()
+                                + items.len().saturating_sub(1);
+                            if length > 255 {
+                                diagnostics.push(Diagnostic::new(
+                                &dropdown.marker,
+                                String::from("enum of the drop-down list must be at most 255 characters including the separators"),
+                            ));
+                            }
+                        }
+                        Some(_) => {}
+                        None => diagnostics.push(Diagnostic::new(
+                            &dropdown.marker,
+                            String::from("'dropdown: true' requires enum"),
+                        )),
+                    }
+                }
+                NodeKind::Scalar(Yaml::Boolean(false)) => {}
+                _ => diagnostics.push(Diagnostic::new(
+                    &dropdown.marker,
+                    format!("dropdown must be boolean, but got {}", dropdown.type_name()),
+                )),
+            }
+        }
+        if let Some(value_styles) = column.get("valueStyles") {
+            if let Some(value_styles) = expect_sequence(value_styles, "valueStyles", diagnostics) {
+                for value_style in value_styles.iter() {
+                    validate_value_style(value_style, diagnostics);
+                }
+            }
+        }
+        if let Some(width) = column.get("width") {
+            let valid = match &width.kind {
+                NodeKind::Scalar(Yaml::Integer(n)) => *n > 0 && *n <= 255,
+                NodeKind::Scalar(Yaml::Real(n)) => {
+                    n.parse::<f64>().is_ok_and(|n| n > 0.0 && n <= 255.0)
+                }
+                _ => false,
+            };
+            if !valid {
+                diagnostics.push(Diagnostic::new(
+                    &width.marker,
+                    String::from("width must be a number greater than 0 and at most 255"),
+                ));
+            }
+        }
+        if let Some(style) = column.get("style") {
+            validate_style(style, "column style", COLUMN_STYLE_KEYS, diagnostics);
+        }
+        if let Some(md) = column.get("md") {
+            match md.as_str() {
+                Some(tag) if !MD_TAGS.contains(&tag) => diagnostics.push(Diagnostic::new(
+                    &md.marker,
+                    format!(
+                        "unknown md tag '{}' (expected one of: {})",
+                        tag,
+                        MD_TAGS.join(", ")
+                    ),
+                )),
+                Some(_) => {}
+                None => expect_string(md, "md", diagnostics),
+            }
+        }
+        if let Some(prefix) = column.get("customPrefix") {
+            match prefix.as_str() {
+                Some(p) if p.is_empty() || p.contains(char::is_whitespace) => {
+                    diagnostics.push(Diagnostic::new(
+                        &prefix.marker,
+                        String::from("customPrefix must not be empty nor contain whitespaces"),
+                    ))
+                }
+                Some(_) => {}
+                None => expect_string(prefix, "customPrefix", diagnostics),
+            }
+        }
+        // find the columns mapped to the same markdown notation
+        if let Some(NodeKind::Scalar(Yaml::Boolean(true))) = column.get("isNum").map(|n| &n.kind) {
+            continue;
+        }
+        let (node, notation) = if let Some(prefix) = column.get("customPrefix") {
+            (
+                prefix,
+                prefix.as_str().map(|p| format!("customPrefix '{}'", p)),
+            )
+        } else if let Some(md) = column.get("md") {
+            (md, md.as_str().map(String::from))
+        } else {
+            continue;
+        };
+        if let (Some(notation), Some(title)) =
+            (notation, column.get("column").and_then(|t| t.as_str()))
+        {
+            match notations.get(&notation) {
+                Some(other) => diagnostics.push(Diagnostic::new(
+                    &node.marker,
+                    format!(
+                        "column '{}' is mapped to {} as well as column '{}'. Use customPrefix, OrderedList or UnorderedList to distinguish them.",
+                        title, notation, other
+                    ),
+                )),
+                None => {
+                    notations.insert(notation, title);
+                }
+            }
+        }
+    }
+    if overflow_required && !overflow_found {
+        diagnostics.push(Diagnostic::new(
+            &block.marker,
+            String::from(
+                "'unmapped: overflow' requires a column with 'overflow: true' in every block",
+            ),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::read_to_string;
+
+    use super::*;
+
+    #[test]
+    fn test_validate_ok() {
+        for path in [
+            "test_case/rule/default_rule.yml",
+            "test_case/rule/general.yml",
+            "test_case/rule/multi_block.yml",
+            "test_case/rule/various_list.yml",
+            "test_case/rule/overflow.yml",
+            "test_case/rule/style.yml",
+            "test_case/rule/filter.yml",
+            "test_rule.yml",
+        ]
+        .iter()
+        {
+            assert_eq!(
+                Vec::<Diagnostic>::new(),
+                validate(&read_to_string(path).unwrap()).unwrap(),
+                "{}",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_empty() {
+        assert_eq!(1, validate("").unwrap().len());
+    }
+
+    #[test]
+    fn test_validate_invalid_key() {
+        let diagnostics =
+            validate(&read_to_string("test_case/rule/invalid_key.yml").unwrap()).unwrap();
+        assert_eq!(
+            vec![Diagnostic {
+                line: 5,
+                column: 9,
+                message: String::from(
+                    "all values of 'content' key must have either keys 'column' or 'group'"
+                ),
+            }],
+            diagnostics
+        );
+    }
+
+    #[test]
+    fn test_validate_schema_errors() {
+        let diagnostics =
+            validate(&read_to_string("test_case/rule/invalid_schema.yml").unwrap()).unwrap();
+        let md_tags = "expected one of: Heading2, Heading3, Heading4, Heading5, Heading6, Heading7, Heading8, List, OrderedList, UnorderedList";
+        let expected = vec![
+            (2, 3, String::from("unknown key 'copyrigt' in general (expected one of: copyright, prefix, unmapped, highlight, title, version, date)")),
+            (8, 9, String::from("unknown key 'colour' in column (expected one of: column, isNum, md, customPrefix, overflow, required, maxLength, pattern, enum, dropdown, valueStyles, width, style)")),
+            (13, 15, format!("unknown md tag 'Heading9' ({})", md_tags)),
+            (16, 18, String::from("isNum must be boolean, but got string")),
+            (15, 15, format!("unknown md tag 'Lst' ({})", md_tags)),
+            (17, 19, String::from("duplicate column title 'Variation 1' in the block")),
+            (20, 16, String::from("only one column can have 'isNum: true' in a block")),
+        ];
+        assert_eq!(
+            expected
+                .into_iter()
+                .map(|(line, column, message)| Diagnostic {
+                    line,
+                    column,
+                    message,
+                })
+                .collect::<Vec<Diagnostic>>(),
+            diagnostics
+        );
+    }
+
+    #[test]
+    fn test_diagnostic_display() {
+        let diagnostic = Diagnostic {
+            line: 3,
+            column: 5,
+            message: String::from("message"),
+        };
+        assert_eq!("line 3, column 5: message", diagnostic.to_string());
+    }
+
+    #[test]
+    fn test_validate_overflow() {
+        let diagnostics =
+            validate(&read_to_string("test_case/rule/invalid_overflow.yml").unwrap()).unwrap();
+        let expected = vec![
+            (
+                9,
+                19,
+                "the column with 'overflow: true' must not have isNum, md nor customPrefix",
+            ),
+            (11, 19, "overflow must be boolean, but got string"),
+            (
+                12,
+                7,
+                "'unmapped: overflow' requires a column with 'overflow: true' in every block",
+            ),
+        ];
+        assert_eq!(
+            expected
+                .into_iter()
+                .map(|(line, column, message)| Diagnostic {
+                    line,
+                    column,
+                    message: String::from(message),
+                })
+                .collect::<Vec<Diagnostic>>(),
+            diagnostics
+        );
+        let diagnostics = validate("general:\n  unmapped: ignore").unwrap();
+        assert_eq!(
+            "line 2, column 13: unknown policy 'ignore' in general.unmapped (expected one of: error, warning, overflow)",
+            diagnostics[0].to_string()
+        );
+    }
+
+    #[test]
+    fn test_validate_layout() {
+        assert!(validate(
+            "layout:\n  origin: B5\n  gap: 0\n  direction: horizontal\ndoc:\n  blocks: []"
+        )
+        .unwrap()
+        .is_empty());
+        let diagnostics = validate(
+            "layout:\n  origin: 5B\n  gap: -1\n  direction: diagonal\n  margin: 1\n  freeze: top",
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                "line 5, column 3: unknown key 'margin' in layout (expected one of: origin, gap, direction, autoFit, freeze)",
+                "line 2, column 11: layout.origin must be a cell like 'A1', but got '5B'",
+                "line 3, column 8: layout.gap must be non-negative integer, but got number",
+                "line 4, column 14: unknown direction 'diagonal' in layout.direction (expected one of: vertical, horizontal)",
+                "line 6, column 11: layout.freeze must be boolean, but got string",
+            ],
+            diagnostics
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<String>>()
+        );
+    }
+
+    #[test]
+    fn test_validate_style() {
+        let diagnostics = validate(
+            "style:\n  title:\n    size: 0\n    bold: yes please\n  footer: {}\ndoc:\n  blocks:\n    - title: Block\n      style:\n        body:\n          color: red\n          border: wavy\n      content:\n      - column: A\n        md: List\n        style:\n          title:\n            font: Meiryo\n          body:\n            valign: middle",
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                "line 5, column 3: unknown key 'footer' in style (expected one of: title, header, body)",
+                "line 3, column 11: style.title.size must be positive number",
+                "line 4, column 11: style.title.bold must be boolean, but got string",
+                "line 11, column 18: block style.body.color must be a color like '#1F4E78', but got 'red'",
+                "line 12, column 19: unknown border 'wavy' in block style.body.border (expected one of: none, thin, medium, thick, dashed, dotted, double, hair)",
+                "line 17, column 11: unknown key 'title' in column style (expected one of: header, body)",
+                "line 20, column 21: unknown valign 'middle' in column style.body.valign (expected one of: top, center, bottom)",
+            ],
+            diagnostics
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<String>>()
+        );
+    }
+
+    #[test]
+    fn test_validate_filter() {
+        let diagnostics = validate(&format!(
+            "doc:\n  blocks:\n    - title: Block\n      autofilter: 1\n      content:\n      - column: A\n        md: List\n        dropdown: true\n        valueStyles:\n          - style:\n              bold: true\n          - value: a\n            pattern: b\n      - column: B\n        md: Heading2\n        enum: [\"{}\", b]\n        dropdown: true\n        valueStyles:\n          - pattern: \"[a-\"\n            style:\n              color: red",
+            "a".repeat(254)
+        ))
+        .unwrap();
+        assert_eq!(
+            vec![
+                "line 4, column 19: autofilter must be boolean, but got number",
+                "line 8, column 19: 'dropdown: true' requires enum",
+                "line 10, column 13: value style must have either value or pattern",
+                "line 12, column 13: value style must have either value or pattern",
+                "line 17, column 19: enum of the drop-down list must be at most 255 characters including the separators",
+                "line 19, column 22: pattern '[a-' is not a valid regular expression",
+                "line 21, column 22: value style.color must be a color like '#1F4E78', but got 'red'",
+            ],
+            diagnostics
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<String>>()
+        );
+    }
+
+    #[test]
+    fn test_validate_cover() {
+        assert!(
+            validate(&read_to_string("test_case/rule/cover.yml").unwrap())
+                .unwrap()
+                .is_empty()
+        );
+        let diagnostics = validate(
+            "general:\n  version: [1]\ncover:\n  name: \"a/b\"\n  countBy: Owner\ndoc:\n  blocks:\n    - title: Block\n      content:\n      - group: G\n        columns:\n        - column: Tester\n          md: List",
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                "line 2, column 12: general.version must be string or number, but got array",
+                "line 4, column 9: cover.name must be 1 to 31 characters without []:*?/\\, but got 'a/b'",
+                "line 5, column 12: cover.countBy refers to unknown column 'Owner'",
+            ],
+            diagnostics
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<String>>()
+        );
+        let diagnostics = validate("cover: 1\ndoc:\n  blocks: []").unwrap();
+        assert_eq!(1, diagnostics.len());
+        assert!(validate("cover: true\ndoc:\n  blocks: []")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_validate_block_id() {
+        let diagnostics = validate(
+            "doc:\n  blocks:\n    - title: A\n      id: a\n    - title: B\n      id: a\n    - title: C\n      id: [c]",
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                "line 6, column 11: duplicate block id 'a'",
+                "line 8, column 11: block id must be string, but got array",
+            ],
+            diagnostics
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<String>>()
+        );
+    }
+
+    #[test]
+    fn test_validate_constraint() {
+        assert!(
+            validate(&read_to_string("test_case/rule/constraint.yml").unwrap())
+                .unwrap()
+                .is_empty()
+        );
+        let diagnostics = validate(
+            "general:\n  highlight: red\ndoc:\n  blocks:\n    - title: Block\n      content:\n      - column: A\n        md: List\n        maxLength: 0\n        pattern: \"[a-\"\n        enum: []\n      - column: B\n        md: Heading2\n        enum:\n          - [a]\n        width: 256",
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                "line 2, column 14: general.highlight must be boolean, but got string",
+                "line 9, column 20: maxLength must be positive integer, but got 0",
+                "line 10, column 18: pattern '[a-' is not a valid regular expression",
+                "line 11, column 15: enum must not be empty",
+                "line 15, column 13: enum values must be scalar, but got array",
+                "line 16, column 16: width must be a number greater than 0 and at most 255",
+            ],
+            diagnostics
+                .iter()
+                .map(|d| d.to_string())
+                .collect::<Vec<String>>()
+        );
+    }
+}
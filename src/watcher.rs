@@ -0,0 +1,71 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Watches files by polling their modification times.
+#[derive(Debug)]
+pub struct Watcher {
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl Watcher {
+    pub fn new(paths: &[&Path]) -> Self {
+        Watcher {
+            files: paths
+                .iter()
+                .map(|path| (path.to_path_buf(), Watcher::modified(path)))
+                .collect(),
+        }
+    }
+
+    /// Returns the files modified since the last call.
+    /// Files being removed are not reported until they're created again,
+    /// since editors often replace the file on save.
+    pub fn changed(&mut self) -> Vec<PathBuf> {
+        let mut changed = vec![];
+        for (path, last_modified) in self.files.iter_mut() {
+            let modified = Watcher::modified(path);
+            if modified.is_some() && modified != *last_modified {
+                changed.push(path.clone());
+            }
+            *last_modified = modified;
+        }
+        changed
+    }
+
+    fn modified(path: &Path) -> Option<SystemTime> {
+        fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_changed() {
+        let path = Path::new("unit_test_watcher.md");
+        fs::write(path, "# Sheet Name").unwrap();
+        let mut watcher = Watcher::new(&[path, Path::new("not_found.md")]);
+        assert!(watcher.changed().is_empty());
+
+        // some file systems have a coarse resolution of modification times
+        std::thread::sleep(Duration::from_millis(1100));
+        fs::write(path, "# Sheet Name 2").unwrap();
+        assert_eq!(vec![path.to_path_buf()], watcher.changed());
+        assert!(watcher.changed().is_empty());
+
+        // removed files are not reported
+        fs::remove_file(path).unwrap();
+        assert!(watcher.changed().is_empty());
+        fs::write(path, "# Sheet Name").unwrap();
+        assert_eq!(vec![path.to_path_buf()], watcher.changed());
+        fs::remove_file(path).unwrap();
+    }
+}